@@ -6,3 +6,16 @@ pub const TOKEN_LAUNCH: &str = "token_launch";
 pub const SNIPE_QUEUE: &str = "snipe_queue";
 pub const METADATA: &str = "metadata";
 pub const SOL_VAULT: &str = "sol_vault";
+pub const VESTING: &str = "vesting";
+pub const REFERRER: &str = "referrer";
+pub const LAUNCH_REGISTRY: &str = "launch_registry";
+pub const CREATOR_INDEX: &str = "creator_index";
+pub const CURVE_ORDER: &str = "curve_order";
+pub const PRICE_OBSERVATIONS: &str = "price_observations";
+pub const TRADER_STATE: &str = "trader_state";
+pub const LAUNCH_STATS: &str = "launch_stats";
+
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+/// quote mint of launches priced in lamports
+pub const NATIVE_MINT: Pubkey = anchor_spl::token::spl_token::native_mint::ID;
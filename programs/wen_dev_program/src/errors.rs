@@ -42,4 +42,80 @@ pub enum WenDevError {
     // 6009
     #[msg("The launch is not completed yet.")]
     NotCompleted,
+
+    // 6010
+    #[msg("MathOverflow")]
+    MathOverflow,
+
+    // 6011
+    #[msg("InsufficientReserves")]
+    InsufficientReserves,
+
+    // 6012
+    #[msg("SnipeNotFound")]
+    SnipeNotFound,
+
+    // 6013
+    #[msg("IncorrectTokenAccount")]
+    IncorrectTokenAccount,
+
+    // 6014
+    #[msg("LaunchNotExpired")]
+    LaunchNotExpired,
+
+    // 6015
+    #[msg("PresaleNotOpen")]
+    PresaleNotOpen,
+
+    // 6016
+    #[msg("PresaleNotEnded")]
+    PresaleNotEnded,
+
+    // 6017
+    #[msg("IncorrectTokenProgram")]
+    IncorrectTokenProgram,
+
+    // 6018
+    #[msg("MissingAccount")]
+    MissingAccount,
+
+    // 6019
+    #[msg("SlippageExceeded")]
+    SlippageExceeded,
+
+    // 6020
+    #[msg("SnipesNotProcessed")]
+    SnipesNotProcessed,
+
+    // 6021
+    #[msg("SelfReferral")]
+    SelfReferral,
+
+    // 6022
+    #[msg("PresaleLimitReached")]
+    PresaleLimitReached,
+
+    // 6023
+    #[msg("DeadlineExceeded")]
+    DeadlineExceeded,
+
+    // 6024
+    #[msg("GraduationExceeded")]
+    GraduationExceeded,
+
+    // 6025
+    #[msg("MaxWalletExceeded")]
+    MaxWalletExceeded,
+
+    // 6026
+    #[msg("SellTooSoonAfterBuy")]
+    SellTooSoonAfterBuy,
+
+    // 6027
+    #[msg("IncorrectAmmAccount")]
+    IncorrectAmmAccount,
+
+    // 6028
+    #[msg("ReferralUnsupported")]
+    ReferralUnsupported,
 }
@@ -0,0 +1,62 @@
+use crate::state::SwapDirection;
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct SnipeSettled {
+    pub token: Pubkey,
+    pub wallet: Pubkey,
+    pub token_amount: u64,
+    /// part of `token_amount` locked in a `VestingSchedule` rather than sent to the wallet
+    pub vested_amount: u64,
+    /// `buy_lamports` moved into the bonding curve sol vault
+    pub filled_lamports: u64,
+    /// `buy_lamports` and `bid_amount` sent back to the sniper
+    pub refunded_lamports: u64,
+    pub creator_bid_lamports: u64,
+    /// platform share of the bid, after the referral fee
+    pub platform_bid_lamports: u64,
+    pub referral_fee_lamports: u64,
+}
+
+#[event]
+pub struct LaunchCancelled {
+    pub token: Pubkey,
+    /// true when cancelled through `expire_launch` rather than by the creator
+    pub expired: bool,
+    pub burned_amount: u64,
+}
+
+#[event]
+pub struct Trade {
+    pub token: Pubkey,
+    pub user: Pubkey,
+    pub direction: SwapDirection,
+    /// lamports added to or taken from the curve, excluding fees
+    pub lamport_amount: u64,
+    /// tokens taken from or added to the curve
+    pub token_amount: u64,
+    /// platform fee, `referral_fee_lamports` included
+    pub fee_lamports: u64,
+    pub referral_fee_lamports: u64,
+    /// paid to the creator by sells too soon after the same wallet's last buy
+    pub penalty_lamports: u64,
+    /// tokens withheld by the Token-2022 transfer fee
+    pub transfer_fee_amount: u64,
+    pub virtual_lamport_reserves: u64,
+    pub virtual_token_reserves: u64,
+    pub real_lamport_reserves: u64,
+}
+
+#[event]
+pub struct ProxySwap {
+    pub amm: Pubkey,
+    pub user: Pubkey,
+    pub direction: SwapDirection,
+    /// quote tokens paid into or out of the pool
+    pub quote_amount_before_fee: u64,
+    /// quote tokens the user paid or received, the platform fee included
+    pub quote_amount_after_fee: u64,
+    pub fee_amount: u64,
+    /// tokens paid out of or into the pool
+    pub token_amount: u64,
+}
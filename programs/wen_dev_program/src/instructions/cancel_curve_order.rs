@@ -0,0 +1,103 @@
+use crate::{
+    constants::CURVE_ORDER,
+    errors::*,
+    state::{CurveOrder, SwapDirection},
+    utils::close_token_escrow,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+#[derive(Accounts)]
+pub struct CancelCurveOrder<'info> {
+    #[account(mut)]
+    owner: Signer<'info>,
+
+    #[account(mut, mint::token_program = token_program)]
+    token: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [
+            CURVE_ORDER.as_bytes(),
+            &token.key().to_bytes(),
+            &owner.key().to_bytes(),
+            &curve_order.order_id.to_le_bytes(),
+        ],
+        bump = curve_order.bump,
+        has_one = owner @ IncorrectAuthority,
+        close = owner,
+    )]
+    curve_order: Box<Account<'info, CurveOrder>>,
+
+    /// only for sell orders
+    #[account(
+        mut,
+        associated_token::mint = token,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program,
+    )]
+    owner_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    #[account(
+        mut,
+        associated_token::mint = token,
+        associated_token::authority = curve_order,
+        associated_token::token_program = token_program,
+    )]
+    order_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    token_program: Interface<'info, TokenInterface>,
+}
+
+/// Cancels an unfilled curve order, returning its escrow, tip and rent to the owner.
+///
+/// Works in any launch phase, so orders left open at migration can still be recovered.
+pub fn cancel_curve_order<'info>(
+    ctx: Context<'_, '_, '_, 'info, CancelCurveOrder<'info>>,
+) -> Result<()> {
+    let owner = &ctx.accounts.owner;
+    let token = &ctx.accounts.token;
+    let curve_order = &ctx.accounts.curve_order;
+
+    if curve_order.direction == SwapDirection::Sell {
+        let (Some(owner_token_account), Some(order_token_account)) = (
+            &ctx.accounts.owner_token_account,
+            &ctx.accounts.order_token_account,
+        ) else {
+            msg!("sell orders need the owner and order token accounts");
+            return Err(MissingAccount.into());
+        };
+        let curve_order_seeds: &[&[&[u8]]] = &[&[
+            CURVE_ORDER.as_bytes(),
+            &token.key().to_bytes(),
+            &owner.key().to_bytes(),
+            &curve_order.order_id.to_le_bytes(),
+            &[curve_order.bump],
+        ]];
+
+        anchor_spl::token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                anchor_spl::token_interface::TransferChecked {
+                    from: order_token_account.to_account_info(),
+                    mint: token.to_account_info(),
+                    to: owner_token_account.to_account_info(),
+                    authority: curve_order.to_account_info(),
+                },
+                curve_order_seeds,
+            ),
+            order_token_account.amount,
+            token.decimals,
+        )?;
+        close_token_escrow(
+            ctx.accounts.token_program.to_account_info(),
+            token.to_account_info(),
+            order_token_account.to_account_info(),
+            owner.to_account_info(),
+            curve_order.to_account_info(),
+            curve_order_seeds,
+        )?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,259 @@
+use crate::{
+    constants::{CREATOR_INDEX, SOL_VAULT, TOKEN_LAUNCH},
+    errors::*,
+    events::LaunchCancelled,
+    state::{CreatorIndex, LaunchPhase, TokenLaunch},
+    utils::{burn_tokens, transfer_quote},
+};
+use anchor_lang::{prelude::*, system_program};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+#[derive(Accounts)]
+pub struct CancelLaunch<'info> {
+    #[account(mut)]
+    creator: Signer<'info>,
+
+    #[account(mut, mint::token_program = token_program)]
+    token: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [TOKEN_LAUNCH.as_bytes(), &token.key().to_bytes()],
+        bump,
+        has_one = creator @ IncorrectAuthority,
+    )]
+    token_launch: Box<Account<'info, TokenLaunch>>,
+
+    #[account(
+        mut,
+        associated_token::mint = token,
+        associated_token::authority = token_launch,
+        associated_token::token_program = token_program,
+    )]
+    launch_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// holds the creator's dev-buy tokens, burned for the refund
+    #[account(
+        mut,
+        token::mint = token,
+        token::authority = creator,
+        token::token_program = token_program,
+    )]
+    creator_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    #[account(
+        mut,
+        seeds = [CREATOR_INDEX.as_bytes(), &token_launch.creator.to_bytes()],
+        bump,
+    )]
+    creator_index: Box<Account<'info, CreatorIndex>>,
+
+    /// CHECK: lamport-only PDA holding the bonding curve reserves
+    #[account(
+        mut,
+        seeds = [SOL_VAULT.as_bytes(), &token.key().to_bytes()],
+        bump,
+    )]
+    sol_vault: UncheckedAccount<'info>,
+
+    /// only for SPL-quoted launches
+    #[account(
+        address = token_launch.quote_mint,
+        mint::token_program = quote_token_program,
+    )]
+    quote_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    #[account(
+        mut,
+        associated_token::mint = quote_mint,
+        associated_token::authority = token_launch,
+        associated_token::token_program = quote_token_program,
+    )]
+    quote_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// receives the refund of the creator's dev buy of SPL-quoted launches
+    #[account(
+        mut,
+        token::mint = quote_mint,
+        token::authority = creator,
+        token::token_program = quote_token_program,
+    )]
+    creator_quote_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    quote_token_program: Option<Interface<'info, TokenInterface>>,
+
+    #[account(address = system_program::ID)]
+    system_program: Program<'info, System>,
+
+    token_program: Interface<'info, TokenInterface>,
+}
+
+pub fn cancel_launch<'info>(ctx: Context<'_, '_, '_, 'info, CancelLaunch<'info>>) -> Result<()> {
+    let accounts = ctx.accounts;
+
+    accounts
+        .token_launch
+        .phase
+        .assert_eq(&LaunchPhase::Presale)?;
+
+    let burned_amount = burn_launch_supply(
+        &accounts.token_program,
+        &accounts.token,
+        &accounts.launch_token_account,
+        &mut accounts.token_launch,
+        ctx.bumps.token_launch,
+    )?;
+    refund_dev_buy(
+        &accounts.system_program,
+        &accounts.token_program,
+        &accounts.token,
+        &accounts.sol_vault,
+        &accounts.creator,
+        &accounts.creator_token_account,
+        &mut accounts.token_launch,
+        ctx.bumps.sol_vault,
+        ctx.bumps.token_launch,
+        &accounts.quote_mint,
+        &accounts.quote_vault,
+        &accounts.creator_quote_account,
+        &accounts.quote_token_program,
+    )?;
+
+    accounts.creator_index.end_presale();
+
+    emit!(LaunchCancelled {
+        token: accounts.token.key(),
+        expired: false,
+        burned_amount,
+    });
+
+    Ok(())
+}
+
+/// Burns everything left in the launch token account and moves the launch to `Cancelled`,
+/// after which snipers can take back their escrow with `reclaim_snipe`.
+pub(crate) fn burn_launch_supply<'info>(
+    token_program: &Interface<'info, TokenInterface>,
+    token: &InterfaceAccount<'info, Mint>,
+    launch_token_account: &InterfaceAccount<'info, TokenAccount>,
+    token_launch: &mut Account<'info, TokenLaunch>,
+    token_launch_bump: u8,
+) -> Result<u64> {
+    let burned_amount = launch_token_account.amount;
+
+    anchor_spl::token_interface::burn(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            anchor_spl::token_interface::Burn {
+                mint: token.to_account_info(),
+                from: launch_token_account.to_account_info(),
+                authority: token_launch.to_account_info(),
+            },
+            &[&[
+                TOKEN_LAUNCH.as_bytes(),
+                &token.key().to_bytes(),
+                &[token_launch_bump],
+            ]],
+        ),
+        burned_amount,
+    )?;
+
+    token_launch.phase = LaunchPhase::Cancelled;
+
+    Ok(burned_amount)
+}
+
+/// Burns the dev-buy tokens the creator still holds and returns the matching share of the dev
+/// buy's lamports from the sol vault, or the quote vault for SPL-quoted launches. The creator must
+/// sign, returns the amount burned.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn refund_dev_buy<'info>(
+    system_program: &Program<'info, System>,
+    token_program: &Interface<'info, TokenInterface>,
+    token: &InterfaceAccount<'info, Mint>,
+    sol_vault: &AccountInfo<'info>,
+    creator: &AccountInfo<'info>,
+    creator_token_account: &Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    token_launch: &mut Account<'info, TokenLaunch>,
+    sol_vault_bump: u8,
+    token_launch_bump: u8,
+    quote_mint: &Option<Box<InterfaceAccount<'info, Mint>>>,
+    quote_vault: &Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    creator_quote_account: &Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    quote_token_program: &Option<Interface<'info, TokenInterface>>,
+) -> Result<u64> {
+    if token_launch.dev_buy_lamports == 0 || token_launch.dev_buy_token_amount == 0 {
+        return Ok(0);
+    }
+    let Some(creator_token_account) = creator_token_account else {
+        msg!("dev buys are refunded against the creator token account");
+        return Err(MissingAccount.into());
+    };
+
+    let burned_amount = creator_token_account
+        .amount
+        .min(token_launch.dev_buy_token_amount);
+    burn_tokens(
+        token_program,
+        token,
+        creator_token_account.to_account_info(),
+        creator.clone(),
+        &[],
+        burned_amount,
+    )?;
+    let refunded_lamports = ((token_launch.dev_buy_lamports as u128) * (burned_amount as u128)
+        / (token_launch.dev_buy_token_amount as u128)) as u64;
+    token_launch.dev_buy_lamports -= refunded_lamports;
+    token_launch.dev_buy_token_amount -= burned_amount;
+
+    if !token_launch.is_native_quote() {
+        let (
+            Some(quote_mint),
+            Some(quote_vault),
+            Some(creator_quote_account),
+            Some(quote_token_program),
+        ) = (
+            quote_mint,
+            quote_vault,
+            creator_quote_account,
+            quote_token_program,
+        )
+        else {
+            msg!("SPL-quoted launches need the quote vault and creator quote account");
+            return Err(MissingAccount.into());
+        };
+
+        transfer_quote(
+            quote_token_program,
+            quote_mint,
+            quote_vault.to_account_info(),
+            creator_quote_account.to_account_info(),
+            token_launch.to_account_info(),
+            &[&[
+                TOKEN_LAUNCH.as_bytes(),
+                &token.key().to_bytes(),
+                &[token_launch_bump],
+            ]],
+            refunded_lamports,
+        )?;
+        return Ok(burned_amount);
+    }
+
+    system_program::transfer(
+        CpiContext::new_with_signer(
+            system_program.to_account_info(),
+            system_program::Transfer {
+                from: sol_vault.clone(),
+                to: creator.clone(),
+            },
+            &[&[
+                SOL_VAULT.as_bytes(),
+                &token.key().to_bytes(),
+                &[sol_vault_bump],
+            ]],
+        ),
+        refunded_lamports,
+    )?;
+
+    Ok(burned_amount)
+}
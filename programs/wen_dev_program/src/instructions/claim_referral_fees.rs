@@ -0,0 +1,36 @@
+use crate::{constants::REFERRER, errors::*, state::Referrer};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct ClaimReferralFees<'info> {
+    #[account(mut)]
+    wallet: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [REFERRER.as_bytes(), &wallet.key().to_bytes()],
+        bump,
+        has_one = wallet @ IncorrectAuthority,
+    )]
+    referrer: Box<Account<'info, Referrer>>,
+}
+
+/// Sends the referral fees collected so far to the referrer wallet
+pub fn claim_referral_fees<'info>(
+    ctx: Context<'_, '_, '_, 'info, ClaimReferralFees<'info>>,
+) -> Result<()> {
+    let referrer = &mut ctx.accounts.referrer;
+
+    let claimable_lamports = referrer.claimable_lamports;
+    if claimable_lamports == 0 {
+        return Err(NothingToDo.into());
+    }
+
+    referrer.claimable_lamports = 0;
+    referrer.claimed_lamports += claimable_lamports;
+
+    referrer.sub_lamports(claimable_lamports)?;
+    ctx.accounts.wallet.add_lamports(claimable_lamports)?;
+
+    Ok(())
+}
@@ -0,0 +1,87 @@
+use crate::{constants::VESTING, errors::*, state::VestingSchedule};
+use anchor_lang::{prelude::*, system_program};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+#[derive(Accounts)]
+pub struct ClaimVested<'info> {
+    #[account(mut)]
+    wallet: Signer<'info>,
+
+    #[account(mint::token_program = token_program)]
+    token: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [VESTING.as_bytes(), &token.key().to_bytes(), &wallet.key().to_bytes()],
+        bump,
+    )]
+    vesting_schedule: Box<Account<'info, VestingSchedule>>,
+
+    #[account(
+        mut,
+        associated_token::mint = token,
+        associated_token::authority = vesting_schedule,
+        associated_token::token_program = token_program,
+    )]
+    vesting_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = wallet,
+        associated_token::mint = token,
+        associated_token::authority = wallet,
+        associated_token::token_program = token_program,
+    )]
+    wallet_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(address = system_program::ID)]
+    system_program: Program<'info, System>,
+
+    token_program: Interface<'info, TokenInterface>,
+
+    #[account(address = anchor_spl::associated_token::ID)]
+    associated_token_program: Program<'info, AssociatedToken>,
+}
+
+pub fn claim_vested<'info>(ctx: Context<'_, '_, '_, 'info, ClaimVested<'info>>) -> Result<()> {
+    let token = &ctx.accounts.token;
+    let wallet = &ctx.accounts.wallet;
+    let vesting_schedule = &mut ctx.accounts.vesting_schedule;
+
+    let now = Clock::get()?.unix_timestamp;
+    let claimable_amount = vesting_schedule
+        .unlocked_amount(now)
+        .saturating_sub(vesting_schedule.claimed_amount)
+        .min(ctx.accounts.vesting_token_account.amount);
+    if claimable_amount == 0 {
+        msg!("nothing unlocked yet for {}", wallet.key());
+        return Err(NothingToDo.into());
+    }
+
+    vesting_schedule.claimed_amount += claimable_amount;
+
+    anchor_spl::token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            anchor_spl::token_interface::TransferChecked {
+                from: ctx.accounts.vesting_token_account.to_account_info(),
+                mint: token.to_account_info(),
+                to: ctx.accounts.wallet_token_account.to_account_info(),
+                authority: vesting_schedule.to_account_info(),
+            },
+            &[&[
+                VESTING.as_bytes(),
+                &token.key().to_bytes(),
+                &wallet.key().to_bytes(),
+                &[ctx.bumps.vesting_schedule],
+            ]],
+        ),
+        claimable_amount,
+        token.decimals,
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,267 @@
+use crate::{
+    constants::{LAUNCH_STATS, PRICE_OBSERVATIONS, SNIPE_QUEUE, SOL_VAULT, TOKEN_LAUNCH},
+    errors::*,
+    state::{LaunchPhase, LaunchStats, PriceObservations, SnipeConfig, SnipeQueue, TokenLaunch},
+    utils::transfer_quote,
+};
+use anchor_lang::{prelude::*, solana_program::program::invoke, system_program, AccountsClose};
+use anchor_spl::{
+    token_2022::spl_token_2022::{extension::transfer_fee, instruction::AuthorityType},
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+#[derive(Accounts)]
+pub struct CloseLaunch<'info> {
+    /// CHECK: paid for the launch accounts, receives their rent
+    #[account(mut, address = token_launch.creator)]
+    creator: UncheckedAccount<'info>,
+
+    #[account(mut, mint::token_program = token_program)]
+    token: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [TOKEN_LAUNCH.as_bytes(), &token.key().to_bytes()],
+        bump,
+    )]
+    token_launch: Box<Account<'info, TokenLaunch>>,
+
+    #[account(
+        mut,
+        seeds = [SNIPE_QUEUE.as_bytes(), &token.key().to_bytes()],
+        bump,
+    )]
+    snipe_queue: Box<Account<'info, SnipeQueue>>,
+
+    #[account(
+        mut,
+        seeds = [PRICE_OBSERVATIONS.as_bytes(), &token.key().to_bytes()],
+        bump,
+    )]
+    price_observations: Box<Account<'info, PriceObservations>>,
+
+    #[account(
+        mut,
+        seeds = [LAUNCH_STATS.as_bytes(), &token.key().to_bytes()],
+        bump,
+    )]
+    launch_stats: Box<Account<'info, LaunchStats>>,
+
+    #[account(
+        mut,
+        associated_token::mint = token,
+        associated_token::authority = token_launch,
+        associated_token::token_program = token_program,
+    )]
+    launch_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: lamport-only PDA that held the bonding curve reserves
+    #[account(
+        mut,
+        seeds = [SOL_VAULT.as_bytes(), &token.key().to_bytes()],
+        bump,
+    )]
+    sol_vault: UncheckedAccount<'info>,
+
+    /// only for SPL-quoted launches
+    #[account(
+        address = token_launch.quote_mint,
+        mint::token_program = quote_token_program,
+    )]
+    quote_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    #[account(
+        mut,
+        associated_token::mint = quote_mint,
+        associated_token::authority = token_launch,
+        associated_token::token_program = quote_token_program,
+    )]
+    quote_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// receives whatever is left in the quote vault of SPL-quoted launches
+    #[account(
+        mut,
+        token::mint = quote_mint,
+        token::authority = creator,
+        token::token_program = quote_token_program,
+    )]
+    creator_quote_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    quote_token_program: Option<Interface<'info, TokenInterface>>,
+
+    #[account(address = system_program::ID)]
+    system_program: Program<'info, System>,
+
+    token_program: Interface<'info, TokenInterface>,
+}
+
+/// Returns the rent of a migrated launch, permissionless.
+///
+/// The wallets of snipers are passed as writable `remaining_accounts`, each gets back the rent
+/// their snipe added to the `SnipeQueue` and is removed from it. Snipers can be paid over several
+/// calls, once the queue is empty the launch accounts are closed and the rest goes to the creator.
+pub fn close_launch<'info>(ctx: Context<'_, '_, '_, 'info, CloseLaunch<'info>>) -> Result<()> {
+    let accounts = ctx.accounts;
+
+    accounts
+        .token_launch
+        .phase
+        .assert_eq(&LaunchPhase::Migrated)?;
+
+    // return each sniper's share of the queue growth
+    let snipe_rent = SnipeQueue::snipe_rent()?;
+    let snipe_queue_info = accounts.snipe_queue.to_account_info();
+    for wallet in ctx.remaining_accounts {
+        let index = accounts
+            .snipe_queue
+            .snipes
+            .iter()
+            .position(|snipe_config| snipe_config.wallet == wallet.key())
+            .ok_or(SnipeNotFound)?;
+        accounts.snipe_queue.snipes.swap_remove(index);
+
+        snipe_queue_info.sub_lamports(snipe_rent)?;
+        wallet.add_lamports(snipe_rent)?;
+    }
+    if !ctx.remaining_accounts.is_empty() {
+        snipe_queue_info.realloc(
+            SnipeQueue::MIN_ACCOUNT_LEN + accounts.snipe_queue.snipes.len() * SnipeConfig::DATA_LEN,
+            false,
+        )?;
+    }
+
+    if !accounts.snipe_queue.snipes.is_empty() {
+        msg!(
+            "{} snipers left to refund before the launch can be closed",
+            accounts.snipe_queue.snipes.len()
+        );
+        return Ok(());
+    }
+
+    let token_launch_seeds: &[&[&[u8]]] = &[&[
+        TOKEN_LAUNCH.as_bytes(),
+        &accounts.token.key().to_bytes(),
+        &[ctx.bumps.token_launch],
+    ]];
+
+    // the launch PDA goes away, so the creator takes over withdrawing transfer fees
+    if accounts.token_launch.transfer_fee.is_some() {
+        invoke(
+            &transfer_fee::instruction::harvest_withheld_tokens_to_mint(
+                &accounts.token_program.key(),
+                &accounts.token.key(),
+                &[&accounts.launch_token_account.key()],
+            )?,
+            &[
+                accounts.token.to_account_info(),
+                accounts.launch_token_account.to_account_info(),
+            ],
+        )?;
+
+        anchor_spl::token_interface::set_authority(
+            CpiContext::new_with_signer(
+                accounts.token_program.to_account_info(),
+                anchor_spl::token_interface::SetAuthority {
+                    current_authority: accounts.token_launch.to_account_info(),
+                    account_or_mint: accounts.token.to_account_info(),
+                },
+                token_launch_seeds,
+            ),
+            AuthorityType::WithheldWithdraw,
+            Some(accounts.creator.key()),
+        )?;
+    }
+
+    // close token accounts, burning or sweeping any dust left in them first
+    anchor_spl::token_interface::burn(
+        CpiContext::new_with_signer(
+            accounts.token_program.to_account_info(),
+            anchor_spl::token_interface::Burn {
+                mint: accounts.token.to_account_info(),
+                from: accounts.launch_token_account.to_account_info(),
+                authority: accounts.token_launch.to_account_info(),
+            },
+            token_launch_seeds,
+        ),
+        accounts.launch_token_account.amount,
+    )?;
+    anchor_spl::token_interface::close_account(CpiContext::new_with_signer(
+        accounts.token_program.to_account_info(),
+        anchor_spl::token_interface::CloseAccount {
+            account: accounts.launch_token_account.to_account_info(),
+            destination: accounts.creator.to_account_info(),
+            authority: accounts.token_launch.to_account_info(),
+        },
+        token_launch_seeds,
+    ))?;
+
+    if !accounts.token_launch.is_native_quote() {
+        let (Some(quote_mint), Some(quote_vault), Some(quote_token_program)) = (
+            &accounts.quote_mint,
+            &accounts.quote_vault,
+            &accounts.quote_token_program,
+        ) else {
+            msg!("SPL-quoted launches need the quote vault and quote token program");
+            return Err(MissingAccount.into());
+        };
+
+        if quote_vault.amount > 0 {
+            let Some(creator_quote_account) = &accounts.creator_quote_account else {
+                msg!(
+                    "the quote vault still holds {}, pass the creator quote account",
+                    quote_vault.amount
+                );
+                return Err(MissingAccount.into());
+            };
+
+            transfer_quote(
+                quote_token_program,
+                quote_mint,
+                quote_vault.to_account_info(),
+                creator_quote_account.to_account_info(),
+                accounts.token_launch.to_account_info(),
+                token_launch_seeds,
+                quote_vault.amount,
+            )?;
+        }
+
+        anchor_spl::token_interface::close_account(CpiContext::new_with_signer(
+            quote_token_program.to_account_info(),
+            anchor_spl::token_interface::CloseAccount {
+                account: quote_vault.to_account_info(),
+                destination: accounts.creator.to_account_info(),
+                authority: accounts.token_launch.to_account_info(),
+            },
+            token_launch_seeds,
+        ))?;
+    }
+
+    // empty the sol vault, normally already drained by `migrate`
+    let sol_vault_lamports = accounts.sol_vault.lamports();
+    if sol_vault_lamports > 0 {
+        system_program::transfer(
+            CpiContext::new_with_signer(
+                accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: accounts.sol_vault.to_account_info(),
+                    to: accounts.creator.to_account_info(),
+                },
+                &[&[
+                    SOL_VAULT.as_bytes(),
+                    &accounts.token.key().to_bytes(),
+                    &[ctx.bumps.sol_vault],
+                ]],
+            ),
+            sol_vault_lamports,
+        )?;
+    }
+
+    // close program accounts
+    let creator = accounts.creator.to_account_info();
+    accounts.snipe_queue.close(creator.clone())?;
+    accounts.price_observations.close(creator.clone())?;
+    accounts.launch_stats.close(creator.clone())?;
+    accounts.token_launch.close(creator)?;
+
+    Ok(())
+}
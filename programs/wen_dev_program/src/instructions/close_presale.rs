@@ -0,0 +1,465 @@
+use crate::{
+    constants::{CREATOR_INDEX, PRICE_OBSERVATIONS, SNIPE_QUEUE, TOKEN_LAUNCH},
+    errors::*,
+    state::{
+        AllocationMode, CreatorIndex, LaunchPhase, PriceObservations, SnipeConfig, SnipeQueue,
+        TokenLaunch,
+    },
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+#[instruction(token: Pubkey)]
+pub struct ClosePresale<'info> {
+    #[account(
+        mut,
+        seeds = [TOKEN_LAUNCH.as_bytes(), &token.to_bytes()],
+        bump,
+    )]
+    token_launch: Box<Account<'info, TokenLaunch>>,
+
+    #[account(
+        mut,
+        seeds = [SNIPE_QUEUE.as_bytes(), &token.to_bytes()],
+        bump,
+    )]
+    snipe_queue: Box<Account<'info, SnipeQueue>>,
+
+    #[account(
+        mut,
+        seeds = [CREATOR_INDEX.as_bytes(), &token_launch.creator.to_bytes()],
+        bump,
+    )]
+    creator_index: Box<Account<'info, CreatorIndex>>,
+
+    #[account(
+        mut,
+        seeds = [PRICE_OBSERVATIONS.as_bytes(), &token.to_bytes()],
+        bump,
+    )]
+    price_observations: Box<Account<'info, PriceObservations>>,
+}
+
+pub fn close_presale<'info>(
+    ctx: Context<'_, '_, '_, 'info, ClosePresale<'info>>,
+    _token: Pubkey,
+) -> Result<()> {
+    let token_launch = &mut ctx.accounts.token_launch;
+    let snipe_queue = &mut ctx.accounts.snipe_queue;
+
+    token_launch.phase.assert_eq(&LaunchPhase::Presale)?;
+    let now = Clock::get()?.unix_timestamp;
+    token_launch.assert_presale_ended(now)?;
+
+    // highest bids first
+    snipe_queue.snipes.sort_by(|a, b| b.cmp(a));
+
+    let (filled_lamports, token_amount) = match token_launch.allocation_mode {
+        AllocationMode::StrictPriority => allocate_strict(token_launch, &mut snipe_queue.snipes)?,
+        AllocationMode::ProRata => allocate_uniform(token_launch, &mut snipe_queue.snipes, 0)?,
+        AllocationMode::Hybrid {
+            guaranteed_lamports,
+        } => allocate_uniform(token_launch, &mut snipe_queue.snipes, guaranteed_lamports)?,
+    };
+    token_launch.apply_buy(filled_lamports, token_amount)?;
+    ctx.accounts.price_observations.record(token_launch);
+
+    token_launch.presale_settled_at = now;
+    token_launch.phase = LaunchPhase::ProcessingPresale;
+    ctx.accounts.creator_index.end_presale();
+
+    Ok(())
+}
+
+/// walks the curve sniper by sniper, each one buying at the price left by the previous, until
+/// the cap or the curve's graduation is reached. Returns the total lamports filled and tokens allocated, for the caller to apply to the curve
+fn allocate_strict(token_launch: &TokenLaunch, snipes: &mut [SnipeConfig]) -> Result<(u64, u64)> {
+    let mut curve = token_launch.clone();
+    let mut remaining_cap = token_launch.presale_token_cap;
+    let mut remaining_lamports = token_launch.lamports_to_graduation();
+
+    for snipe in snipes.iter_mut() {
+        // buys are cut short at graduation, like curve swaps
+        let buy_lamports = snipe.buy_lamports.min(remaining_lamports);
+        let mut token_amount = curve.quote_buy(buy_lamports)?;
+        let mut filled_lamports = buy_lamports;
+
+        if token_amount > remaining_cap {
+            token_amount = remaining_cap;
+            filled_lamports = curve
+                .quote_lamports_for_tokens(token_amount)?
+                .min(buy_lamports);
+        }
+        if token_amount == 0 {
+            // nothing left to buy, the whole snipe is refunded
+            filled_lamports = 0;
+        }
+
+        curve.virtual_lamport_reserves = curve
+            .virtual_lamport_reserves
+            .checked_add(filled_lamports)
+            .ok_or(MathOverflow)?;
+        curve.virtual_token_reserves -= token_amount;
+        remaining_cap -= token_amount;
+        remaining_lamports -= filled_lamports;
+
+        snipe.token_amount = token_amount;
+        snipe.refund_lamports = snipe.buy_lamports - filled_lamports;
+    }
+
+    Ok((
+        curve.virtual_lamport_reserves - token_launch.virtual_lamport_reserves,
+        token_launch.virtual_token_reserves - curve.virtual_token_reserves,
+    ))
+}
+
+/// fills every sniper at the same average price, first up to `guaranteed_lamports` each,
+/// then sharing whatever is left of the cap in proportion to the remaining `buy_lamports`.
+/// The fill stops at the curve's graduation. Returns the total lamports filled and tokens allocated, for the caller to apply to the curve
+fn allocate_uniform(
+    token_launch: &TokenLaunch,
+    snipes: &mut [SnipeConfig],
+    guaranteed_lamports: u64,
+) -> Result<(u64, u64)> {
+    let mut demand = 0u64;
+    let mut guaranteed_demand = 0u64;
+    for snipe in snipes.iter() {
+        demand = demand.checked_add(snipe.buy_lamports).ok_or(MathOverflow)?;
+        guaranteed_demand = guaranteed_demand
+            .checked_add(snipe.buy_lamports.min(guaranteed_lamports))
+            .ok_or(MathOverflow)?;
+    }
+
+    if demand == 0 {
+        return Ok((0, 0));
+    }
+
+    // total lamports and tokens that the cap and graduation allow to be filled
+    let budget = demand.min(token_launch.lamports_to_graduation());
+    let full_token_amount = token_launch.quote_buy(budget)?;
+    let (fill_lamports, fill_tokens) = if full_token_amount <= token_launch.presale_token_cap {
+        (budget, full_token_amount)
+    } else {
+        let cap = token_launch.presale_token_cap;
+        (
+            token_launch.quote_lamports_for_tokens(cap)?.min(budget),
+            cap,
+        )
+    };
+
+    let mut total_filled_lamports = 0u64;
+    let mut total_token_amount = 0u64;
+    for snipe in snipes.iter_mut() {
+        let guaranteed = snipe.buy_lamports.min(guaranteed_lamports);
+        let filled_lamports = if guaranteed_demand >= fill_lamports {
+            pro_rata(guaranteed, fill_lamports, guaranteed_demand)?
+        } else {
+            let remainder = pro_rata(
+                snipe.buy_lamports - guaranteed,
+                fill_lamports - guaranteed_demand,
+                demand - guaranteed_demand,
+            )?;
+            guaranteed + remainder
+        };
+        let token_amount = pro_rata(filled_lamports, fill_tokens, fill_lamports)?;
+        let filled_lamports = if token_amount == 0 {
+            0
+        } else {
+            filled_lamports
+        };
+
+        total_filled_lamports += filled_lamports;
+        total_token_amount += token_amount;
+
+        snipe.token_amount = token_amount;
+        snipe.refund_lamports = snipe.buy_lamports - filled_lamports;
+    }
+
+    Ok((total_filled_lamports, total_token_amount))
+}
+
+/// `amount * numerator / denominator`, rounded down
+fn pro_rata(amount: u64, numerator: u64, denominator: u64) -> Result<u64> {
+    if denominator == 0 {
+        return Ok(0);
+    }
+
+    let result = (amount as u128) * (numerator as u128) / (denominator as u128);
+    u64::try_from(result).map_err(|_| MathOverflow.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{tests::launch, CurveKind};
+
+    const SOL: u64 = 1_000_000_000;
+
+    fn snipe(bid_amount: u64, buy_lamports: u64) -> SnipeConfig {
+        SnipeConfig {
+            wallet: Pubkey::new_unique(),
+            bid_amount,
+            buy_lamports,
+            token_amount: 0,
+            refund_lamports: 0,
+            processed: false,
+            referrer: None,
+        }
+    }
+
+    /// allocates `(bid_amount, buy_lamports)` snipes like `close_presale`, checking what every
+    /// mode must hold
+    fn allocate(
+        allocation_mode: AllocationMode,
+        presale_token_cap: u64,
+        snipes: &[(u64, u64)],
+    ) -> Vec<SnipeConfig> {
+        let mut token_launch = launch(CurveKind::ConstantProduct);
+        token_launch.presale_token_cap = presale_token_cap;
+        allocate_on(&token_launch, allocation_mode, snipes)
+    }
+
+    fn allocate_on(
+        token_launch: &TokenLaunch,
+        allocation_mode: AllocationMode,
+        snipes: &[(u64, u64)],
+    ) -> Vec<SnipeConfig> {
+        let presale_token_cap = token_launch.presale_token_cap;
+        let mut snipes: Vec<SnipeConfig> = snipes
+            .iter()
+            .map(|&(bid_amount, buy_lamports)| snipe(bid_amount, buy_lamports))
+            .collect();
+        snipes.sort_by(|a, b| b.cmp(a));
+
+        let (filled_lamports, token_amount) = match allocation_mode {
+            AllocationMode::StrictPriority => allocate_strict(token_launch, &mut snipes),
+            AllocationMode::ProRata => allocate_uniform(token_launch, &mut snipes, 0),
+            AllocationMode::Hybrid {
+                guaranteed_lamports,
+            } => allocate_uniform(token_launch, &mut snipes, guaranteed_lamports),
+        }
+        .unwrap();
+
+        assert!(token_amount <= presale_token_cap);
+        assert!(filled_lamports <= token_launch.lamports_to_graduation());
+        assert_eq!(
+            token_amount,
+            snipes.iter().map(|snipe| snipe.token_amount).sum::<u64>()
+        );
+        assert_eq!(
+            filled_lamports,
+            snipes
+                .iter()
+                .map(|snipe| snipe.buy_lamports - snipe.refund_lamports)
+                .sum::<u64>()
+        );
+        for snipe in &snipes {
+            assert!(snipe.refund_lamports <= snipe.buy_lamports);
+            if snipe.token_amount == 0 {
+                assert_eq!(snipe.refund_lamports, snipe.buy_lamports);
+            }
+        }
+        // the curve is paid at least its price for the tokens handed out
+        assert!(token_launch.quote_buy(filled_lamports).unwrap() >= token_amount);
+
+        snipes
+    }
+
+    #[test]
+    fn strict_priority_fills_everyone_when_undersubscribed() {
+        let snipes = allocate(
+            AllocationMode::StrictPriority,
+            u64::MAX,
+            &[(1, SOL), (3, SOL), (2, SOL)],
+        );
+
+        assert!(snipes.iter().all(|snipe| snipe.refund_lamports == 0));
+        // each sniper pays the price left by the higher bid before it
+        assert_eq!(
+            snipes
+                .iter()
+                .map(|snipe| snipe.bid_amount)
+                .collect::<Vec<_>>(),
+            [3, 2, 1]
+        );
+        assert!(snipes[0].token_amount > snipes[1].token_amount);
+        assert!(snipes[1].token_amount > snipes[2].token_amount);
+    }
+
+    #[test]
+    fn strict_priority_cuts_off_lower_bids_when_oversubscribed() {
+        let token_launch = launch(CurveKind::ConstantProduct);
+        let cap = token_launch.quote_buy(3 * SOL / 2).unwrap();
+        let snipes = allocate(
+            AllocationMode::StrictPriority,
+            cap,
+            &[(1, SOL), (3, SOL), (2, SOL)],
+        );
+
+        // the top bid is filled, the next one gets the rest of the cap, the last is refunded
+        assert_eq!(snipes[0].refund_lamports, 0);
+        assert!(snipes[1].token_amount > 0);
+        assert!(snipes[1].refund_lamports.abs_diff(SOL / 2) <= 1);
+        assert_eq!(snipes[2].token_amount, 0);
+        assert_eq!(
+            snipes.iter().map(|snipe| snipe.token_amount).sum::<u64>(),
+            cap
+        );
+    }
+
+    #[test]
+    fn pro_rata_fills_everyone_when_undersubscribed() {
+        let snipes = allocate(
+            AllocationMode::ProRata,
+            u64::MAX,
+            &[(1, SOL), (3, 2 * SOL), (2, 4 * SOL)],
+        );
+
+        assert!(snipes.iter().all(|snipe| snipe.refund_lamports == 0));
+        // one price for everyone, the queue is sorted by bid
+        let sol_tokens = snipes[0].token_amount / 2;
+        assert!(snipes[1].token_amount.abs_diff(sol_tokens * 4) <= 4);
+        assert!(snipes[2].token_amount.abs_diff(sol_tokens) <= 1);
+    }
+
+    #[test]
+    fn pro_rata_shares_the_cap_when_oversubscribed() {
+        let cap = 10_000_000_000_000;
+        let snipes = allocate(
+            AllocationMode::ProRata,
+            cap,
+            &[(1, SOL), (3, 2 * SOL), (2, 4 * SOL)],
+        );
+
+        // shares follow `buy_lamports` regardless of the bid, up to the tokens of a lamport lost
+        // to rounding
+        let tolerance = cap / 1_000_000;
+        let total = snipes.iter().map(|snipe| snipe.token_amount).sum::<u64>();
+        assert!(total > cap - tolerance);
+        for (snipe, share) in snipes.iter().zip([2, 4, 1]) {
+            assert!(snipe.token_amount.abs_diff(cap * share / 7) <= tolerance);
+            assert!(snipe.refund_lamports > 0);
+        }
+    }
+
+    #[test]
+    fn pro_rata_refunds_snipes_rounded_down_to_no_tokens() {
+        let token_launch = launch(CurveKind::ConstantProduct);
+        // a cap so small that a 1 lamport snipe's share rounds down to nothing
+        let cap = token_launch.quote_buy(1_000).unwrap();
+        let snipes = allocate(AllocationMode::ProRata, cap, &[(2, SOL), (1, 1)]);
+
+        assert!(snipes[0].token_amount > 0);
+        assert_eq!(snipes[1].token_amount, 0);
+        assert_eq!(snipes[1].refund_lamports, 1);
+    }
+
+    #[test]
+    fn hybrid_fills_guarantees_first_when_oversubscribed() {
+        let token_launch = launch(CurveKind::ConstantProduct);
+        let cap = token_launch.quote_buy(3 * SOL).unwrap();
+        let snipes = allocate(
+            AllocationMode::Hybrid {
+                guaranteed_lamports: SOL,
+            },
+            cap,
+            &[(3, 5 * SOL), (2, 3 * SOL), (1, SOL / 2)],
+        );
+
+        // the small snipe fits in the guarantee and is filled in full, the others share what is
+        // left after their guarantees in proportion to the rest of their buys
+        assert_eq!(snipes[2].refund_lamports, 0);
+        let filled = |snipe: &SnipeConfig| snipe.buy_lamports - snipe.refund_lamports;
+        let (remainder_0, remainder_1) = (filled(&snipes[0]) - SOL, filled(&snipes[1]) - SOL);
+        assert!((remainder_0 as f64 / remainder_1 as f64 - 2.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn hybrid_shares_guarantees_when_they_exceed_the_cap() {
+        let token_launch = launch(CurveKind::ConstantProduct);
+        let cap = token_launch.quote_buy(SOL).unwrap();
+        let snipes = allocate(
+            AllocationMode::Hybrid {
+                guaranteed_lamports: SOL,
+            },
+            cap,
+            &[(3, 5 * SOL), (2, SOL), (1, SOL / 2)],
+        );
+
+        // guarantees of 1, 1 and 0.5 SOL are scaled down together
+        assert_eq!(snipes[0].token_amount, snipes[1].token_amount);
+        assert!(snipes[2].token_amount.abs_diff(snipes[1].token_amount / 2) <= 1);
+    }
+
+    #[test]
+    fn allocations_never_exceed_the_cap() {
+        // deterministic pseudo-random queues, checked by `allocate`
+        let mut seed = 0x2545_f491_4f6c_dd1du64;
+        let mut next = |max: u64| {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            seed % max
+        };
+
+        for _ in 0..200 {
+            let snipes: Vec<(u64, u64)> = (0..1 + next(20))
+                .map(|_| (next(5), 1 + next(10 * SOL)))
+                .collect();
+            let cap = next(200_000_000_000_000);
+            for allocation_mode in [
+                AllocationMode::StrictPriority,
+                AllocationMode::ProRata,
+                AllocationMode::Hybrid {
+                    guaranteed_lamports: next(2 * SOL),
+                },
+            ] {
+                allocate(allocation_mode, cap, &snipes);
+            }
+        }
+    }
+
+    #[test]
+    fn allocations_stop_at_graduation() {
+        // a 2 SOL dev buy leaves 3 SOL before the 5 SOL graduation, snipers ask for 10
+        let mut token_launch = launch(CurveKind::ConstantProduct);
+        token_launch.presale_token_cap = u64::MAX;
+        token_launch.graduation_lamports = 5 * SOL;
+        token_launch.real_lamport_reserves = 2 * SOL;
+
+        for allocation_mode in [
+            AllocationMode::StrictPriority,
+            AllocationMode::ProRata,
+            AllocationMode::Hybrid {
+                guaranteed_lamports: SOL,
+            },
+        ] {
+            let snipes = allocate_on(
+                &token_launch,
+                allocation_mode,
+                &[(3, 2 * SOL), (2, 6 * SOL), (1, 2 * SOL)],
+            );
+
+            // the rest is refunded, less at most a lamport per sniper lost to rounding
+            let refunded = snipes
+                .iter()
+                .map(|snipe| snipe.refund_lamports)
+                .sum::<u64>();
+            assert!((7 * SOL..=7 * SOL + 3).contains(&refunded));
+        }
+
+        // strict priority fills the top bids and lands exactly on graduation
+        let snipes = allocate_on(
+            &token_launch,
+            AllocationMode::StrictPriority,
+            &[(3, 2 * SOL), (2, 6 * SOL), (1, 2 * SOL)],
+        );
+        assert_eq!(
+            snipes
+                .iter()
+                .map(|snipe| snipe.refund_lamports)
+                .collect::<Vec<_>>(),
+            [0, 5 * SOL, 2 * SOL]
+        );
+    }
+}
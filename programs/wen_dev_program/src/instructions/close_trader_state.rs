@@ -0,0 +1,52 @@
+use crate::{
+    constants::{TOKEN_LAUNCH, TRADER_STATE},
+    errors::*,
+    state::{LaunchPhase, TokenLaunch, TraderState},
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+#[instruction(token: Pubkey)]
+pub struct CloseTraderState<'info> {
+    #[account(mut)]
+    wallet: Signer<'info>,
+
+    /// CHECK: `close_launch` closes the launch once migrated, read in the handler while it exists
+    #[account(
+        seeds = [TOKEN_LAUNCH.as_bytes(), &token.to_bytes()],
+        bump,
+    )]
+    token_launch: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        close = wallet,
+        seeds = [TRADER_STATE.as_bytes(), &token.to_bytes(), &wallet.key().to_bytes()],
+        bump,
+    )]
+    trader_state: Box<Account<'info, TraderState>>,
+}
+
+/// Returns the rent of a wallet's `TraderState` once its launch no longer trades on the curve,
+/// the sandwich window has nothing left to guard by then.
+pub fn close_trader_state<'info>(
+    ctx: Context<'_, '_, '_, 'info, CloseTraderState<'info>>,
+    _token: Pubkey,
+) -> Result<()> {
+    let token_launch = &ctx.accounts.token_launch;
+
+    // a closed launch account was migrated first
+    if !token_launch.data_is_empty() {
+        match TokenLaunch::try_deserialize(&mut &token_launch.try_borrow_data()?[..])?.phase {
+            LaunchPhase::Completed | LaunchPhase::Migrated | LaunchPhase::Cancelled => {}
+            phase => {
+                msg!(&format!(
+                    "trader states of {phase:?} launches cannot be closed"
+                ));
+                return Err(IncorrectLaunchPhase.into());
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -25,6 +25,8 @@ pub fn configure<'info>(
     let payer = &ctx.accounts.payer;
     let config = &ctx.accounts.config;
 
+    new_config.validate()?;
+
     let serialized_config = [&Config::DISCRIMINATOR, new_config.try_to_vec()?.as_slice()].concat();
     let serialized_config_len = serialized_config.len();
     let config_cost = Rent::get()?.minimum_balance(serialized_config_len);
@@ -1,12 +1,14 @@
+use crate::utils::{amm_authority, assert_amm_accounts, load_amm_keys};
 use amm_anchor::Deposit;
 use anchor_lang::prelude::*;
 use anchor_spl::token::Token;
 #[derive(Accounts, Clone)]
 pub struct ProxyDeposit<'info> {
-    /// CHECK: Safe
+    /// CHECK: Safe. The AMM program
+    #[account(address = amm_anchor::ID)]
     pub amm_program: UncheckedAccount<'info>,
     /// CHECK: Safe. Amm Account
-    #[account(mut)]
+    #[account(mut, owner = amm_anchor::ID)]
     pub amm: UncheckedAccount<'info>,
     /// CHECK: Safe. Amm authority, a PDA create with seed = [b"amm authority"]
     #[account()]
@@ -71,12 +73,32 @@ impl<'a, 'b, 'c, 'info> From<&mut ProxyDeposit<'info>>
     }
 }
 
+impl ProxyDeposit<'_> {
+    /// checks the pool accounts against the pool's `AmmInfo`
+    fn validate(&self) -> Result<()> {
+        let amm_keys = load_amm_keys(&self.amm)?;
+        assert_amm_accounts(
+            &self.amm,
+            &[
+                (self.amm_authority.key(), amm_authority(&amm_keys)?),
+                (self.amm_open_orders.key(), amm_keys.open_orders),
+                (self.amm_target_orders.key(), amm_keys.target_orders),
+                (self.amm_lp_mint.key(), amm_keys.lp_mint),
+                (self.amm_coin_vault.key(), amm_keys.coin_vault),
+                (self.amm_pc_vault.key(), amm_keys.pc_vault),
+                (self.market.key(), amm_keys.market),
+            ],
+        )
+    }
+}
+
 pub fn deposit(
     ctx: Context<ProxyDeposit>,
     max_coin_amount: u64,
     max_pc_amount: u64,
     base_side: u64,
 ) -> Result<()> {
+    ctx.accounts.validate()?;
     amm_anchor::deposit(
         ctx.accounts.into(),
         max_coin_amount,
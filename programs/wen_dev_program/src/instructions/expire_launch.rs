@@ -0,0 +1,88 @@
+use crate::{
+    constants::{CONFIG, CREATOR_INDEX, TOKEN_LAUNCH},
+    errors::*,
+    events::LaunchCancelled,
+    instructions::burn_launch_supply,
+    state::{Config, CreatorIndex, LaunchPhase, TokenLaunch},
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+#[derive(Accounts)]
+pub struct ExpireLaunch<'info> {
+    #[account(seeds = [CONFIG.as_bytes()], bump)]
+    config: Box<Account<'info, Config>>,
+
+    #[account(mut, mint::token_program = token_program)]
+    token: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [TOKEN_LAUNCH.as_bytes(), &token.key().to_bytes()],
+        bump,
+    )]
+    token_launch: Box<Account<'info, TokenLaunch>>,
+
+    #[account(
+        mut,
+        associated_token::mint = token,
+        associated_token::authority = token_launch,
+        associated_token::token_program = token_program,
+    )]
+    launch_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [CREATOR_INDEX.as_bytes(), &token_launch.creator.to_bytes()],
+        bump,
+    )]
+    creator_index: Box<Account<'info, CreatorIndex>>,
+
+    token_program: Interface<'info, TokenInterface>,
+}
+
+/// Cancels a launch that was left in `Presale` or `ProcessingPresale` past its expiry,
+/// permissionless. The creator takes back a dev buy with `reclaim_dev_buy`.
+pub fn expire_launch<'info>(ctx: Context<'_, '_, '_, 'info, ExpireLaunch<'info>>) -> Result<()> {
+    let accounts = ctx.accounts;
+
+    // a launch whose presale settlement stalled expires on the same deadline
+    let in_presale = match &accounts.token_launch.phase {
+        LaunchPhase::Presale => true,
+        LaunchPhase::ProcessingPresale => false,
+        phase => {
+            msg!(&format!("{phase:?} launches cannot expire"));
+            return Err(IncorrectLaunchPhase.into());
+        }
+    };
+
+    let expires_at = accounts
+        .token_launch
+        .presale_end
+        .saturating_add(accounts.config.launch_expiry_seconds);
+    if Clock::get()?.unix_timestamp < expires_at {
+        msg!(&format!("launch expires at {expires_at}"));
+        return Err(LaunchNotExpired.into());
+    }
+
+    let burned_amount = burn_launch_supply(
+        &accounts.token_program,
+        &accounts.token,
+        &accounts.launch_token_account,
+        &mut accounts.token_launch,
+        ctx.bumps.token_launch,
+    )?;
+
+    // `close_presale` already ended the presale of launches stuck processing it
+    if in_presale {
+        accounts.creator_index.end_presale();
+    }
+
+    emit!(LaunchCancelled {
+        token: accounts.token.key(),
+        expired: true,
+        burned_amount,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,410 @@
+use crate::{
+    constants::{
+        CONFIG, CURVE_ORDER, LAUNCH_STATS, PRICE_OBSERVATIONS, SOL_VAULT, TOKEN_LAUNCH,
+        TRADER_STATE,
+    },
+    errors::*,
+    events::Trade,
+    state::{
+        Config, CurveOrder, LaunchPhase, LaunchStats, PriceObservations, SwapDirection,
+        TokenLaunch, TraderState,
+    },
+    utils::{bps_of, close_token_escrow, create_pda_account, transfer_fee},
+};
+use anchor_lang::{prelude::*, system_program, AccountsClose, Discriminator};
+use anchor_spl::{
+    associated_token::get_associated_token_address_with_program_id,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+#[derive(Accounts)]
+pub struct FillCurveOrders<'info> {
+    /// receives the tip of every filled order
+    #[account(mut)]
+    cranker: Signer<'info>,
+
+    #[account(seeds = [CONFIG.as_bytes()], bump)]
+    config: Box<Account<'info, Config>>,
+
+    /// CHECK: receives platform fees
+    #[account(mut, address = config.fee_wallet)]
+    fee_wallet: UncheckedAccount<'info>,
+
+    #[account(mut, mint::token_program = token_program)]
+    token: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [TOKEN_LAUNCH.as_bytes(), &token.key().to_bytes()],
+        bump,
+    )]
+    token_launch: Box<Account<'info, TokenLaunch>>,
+
+    /// CHECK: receives sandwich penalties of sell orders
+    #[account(mut, address = token_launch.creator)]
+    creator: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        associated_token::mint = token,
+        associated_token::authority = token_launch,
+        associated_token::token_program = token_program,
+    )]
+    launch_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [PRICE_OBSERVATIONS.as_bytes(), &token.key().to_bytes()],
+        bump,
+    )]
+    price_observations: Box<Account<'info, PriceObservations>>,
+
+    #[account(
+        mut,
+        seeds = [LAUNCH_STATS.as_bytes(), &token.key().to_bytes()],
+        bump,
+    )]
+    launch_stats: Box<Account<'info, LaunchStats>>,
+
+    /// CHECK: lamport-only PDA holding the bonding curve reserves
+    #[account(
+        mut,
+        seeds = [SOL_VAULT.as_bytes(), &token.key().to_bytes()],
+        bump,
+    )]
+    sol_vault: UncheckedAccount<'info>,
+
+    #[account(address = system_program::ID)]
+    system_program: Program<'info, System>,
+
+    token_program: Interface<'info, TokenInterface>,
+}
+
+/// Fills the curve orders whose limit the curve has reached, permissionless.
+///
+/// `remaining_accounts` holds one writable `(curve order, owner, token account, trader state)`
+/// group per order, the token account being the owner's associated token account for buys and the
+/// order's escrow for sells. Orders are filled in the order given, each moving the curve for the
+/// next, and those not at their limit are skipped. Buys that would pass graduation are cut short
+/// and the unspent escrow is refunded. Filled orders are closed to their owner and their tip paid
+/// to the cranker.
+///
+/// Filled buys record their slot in the owner's `TraderState`, creating it out of the order's
+/// escrow if needed, and sells inside the sandwich window pay the penalty or are skipped, as for
+/// `swap`.
+pub fn fill_curve_orders<'info>(
+    ctx: Context<'_, '_, 'info, 'info, FillCurveOrders<'info>>,
+) -> Result<()> {
+    let token = &ctx.accounts.token;
+    let token_launch = &mut ctx.accounts.token_launch;
+
+    token_launch.phase.assert_eq(&LaunchPhase::OpenSale)?;
+
+    let groups = ctx.remaining_accounts.chunks_exact(4);
+    if ctx.remaining_accounts.is_empty() || !groups.remainder().is_empty() {
+        msg!("expected 4 accounts per order in remaining accounts");
+        return Err(ValueInvalid.into());
+    }
+
+    let token_launch_seeds: &[&[&[u8]]] = &[&[
+        TOKEN_LAUNCH.as_bytes(),
+        &token.key().to_bytes(),
+        &[ctx.bumps.token_launch],
+    ]];
+    let sol_vault_seeds: &[&[&[u8]]] = &[&[
+        SOL_VAULT.as_bytes(),
+        &token.key().to_bytes(),
+        &[ctx.bumps.sol_vault],
+    ]];
+
+    let clock = Clock::get()?;
+    let now = clock.unix_timestamp;
+    let mut filled_orders = 0;
+    for accounts in groups {
+        // the curve only trades until it completes
+        if token_launch.phase != LaunchPhase::OpenSale {
+            break;
+        }
+
+        let (owner, token_account, trader_state) = (&accounts[1], &accounts[2], &accounts[3]);
+        let curve_order = Account::<CurveOrder>::try_from(&accounts[0])?;
+        if curve_order.token != token.key() || curve_order.owner != owner.key() {
+            msg!(
+                "{} is not an order of {} on this launch",
+                curve_order.key(),
+                owner.key()
+            );
+            return Err(ValueInvalid.into());
+        }
+        let trader_state_bump = trader_state_bump(&token.key(), owner, trader_state)?;
+
+        let mut penalty_lamports = 0;
+        let (lamport_amount, token_amount, fee_lamports, transfer_fee_amount) = match curve_order
+            .direction
+        {
+            SwapDirection::Buy => {
+                let lamport_amount = curve_order
+                    .amount_in
+                    .min(token_launch.lamports_to_graduation());
+                let token_amount = token_launch.quote_buy(lamport_amount)?;
+                let transfer_fee_amount = transfer_fee(&token.to_account_info(), token_amount)?;
+
+                // the limit holds pro rata when the buy is cut short
+                if ((token_amount - transfer_fee_amount) as u128) * (curve_order.amount_in as u128)
+                    < (curve_order.limit_amount_out as u128) * (lamport_amount as u128)
+                    || lamport_amount == 0
+                {
+                    msg!("{} is not at its limit, skipping", curve_order.key());
+                    continue;
+                }
+
+                let expected_token_account = get_associated_token_address_with_program_id(
+                    &owner.key(),
+                    &token.key(),
+                    &ctx.accounts.token_program.key(),
+                );
+                if token_account.key() != expected_token_account {
+                    msg!("expected associated token account of {}", owner.key());
+                    return Err(IncorrectTokenAccount.into());
+                }
+
+                // orders stay open until the wallet cap lifts
+                let balance = InterfaceAccount::<TokenAccount>::try_from(token_account)?.amount;
+                if token_launch
+                    .max_wallet_amount(now)
+                    .is_some_and(|max_amount| {
+                        balance + token_amount - transfer_fee_amount > max_amount
+                    })
+                {
+                    msg!(
+                        "{} would exceed the wallet cap, skipping",
+                        curve_order.key()
+                    );
+                    continue;
+                }
+
+                let fee_lamports = bps_of(lamport_amount, curve_order.fee_bps);
+                token_launch.apply_buy(lamport_amount, token_amount)?;
+
+                // the owner's receipt is paid for out of the order, like the fill itself
+                if trader_state.data_is_empty() {
+                    let rent_lamports = Rent::get()?.minimum_balance(TraderState::ACCOUNT_LEN);
+                    let funded_lamports = rent_lamports.saturating_sub(trader_state.lamports());
+                    curve_order.sub_lamports(funded_lamports)?;
+                    trader_state.add_lamports(funded_lamports)?;
+                    create_pda_account(
+                        ctx.accounts.cranker.to_account_info(),
+                        trader_state.clone(),
+                        ctx.accounts.system_program.to_account_info(),
+                        TraderState::ACCOUNT_LEN,
+                        &[&[
+                            TRADER_STATE.as_bytes(),
+                            &token.key().to_bytes(),
+                            &owner.key().to_bytes(),
+                            &[trader_state_bump],
+                        ]],
+                    )?;
+                    trader_state.try_borrow_mut_data()?[..8]
+                        .copy_from_slice(&TraderState::DISCRIMINATOR);
+                    ctx.accounts.launch_stats.unique_traders += 1;
+                }
+                let mut owner_state = Account::<TraderState>::try_from(trader_state)?;
+                owner_state.token = token.key();
+                owner_state.wallet = owner.key();
+                owner_state.last_buy_slot = Some(clock.slot);
+                owner_state.exit(&crate::ID)?;
+
+                curve_order.sub_lamports(lamport_amount + fee_lamports)?;
+                ctx.accounts.sol_vault.add_lamports(lamport_amount)?;
+                ctx.accounts.fee_wallet.add_lamports(fee_lamports)?;
+
+                anchor_spl::token_interface::transfer_checked(
+                    CpiContext::new_with_signer(
+                        ctx.accounts.token_program.to_account_info(),
+                        anchor_spl::token_interface::TransferChecked {
+                            from: ctx.accounts.launch_token_account.to_account_info(),
+                            mint: token.to_account_info(),
+                            to: token_account.clone(),
+                            authority: token_launch.to_account_info(),
+                        },
+                        token_launch_seeds,
+                    ),
+                    token_amount,
+                    token.decimals,
+                )?;
+
+                if token_launch.lamports_to_graduation() == 0 {
+                    token_launch.phase = LaunchPhase::Completed;
+                }
+
+                (
+                    lamport_amount,
+                    token_amount,
+                    fee_lamports,
+                    transfer_fee_amount,
+                )
+            }
+            SwapDirection::Sell => {
+                let expected_token_account = get_associated_token_address_with_program_id(
+                    &curve_order.key(),
+                    &token.key(),
+                    &ctx.accounts.token_program.key(),
+                );
+                if token_account.key() != expected_token_account {
+                    msg!("expected escrow token account of {}", curve_order.key());
+                    return Err(IncorrectTokenAccount.into());
+                }
+
+                // sell the whole escrow, the escrow could not be closed with anything left in it
+                let escrow_amount =
+                    InterfaceAccount::<TokenAccount>::try_from(token_account)?.amount;
+                // the curve is only credited with what arrives after the transfer fee
+                let transfer_fee_amount = transfer_fee(&token.to_account_info(), escrow_amount)?;
+                let token_amount = escrow_amount - transfer_fee_amount;
+                let lamport_amount = token_launch.quote_sell(token_amount)?;
+                let fee_lamports = bps_of(lamport_amount, curve_order.fee_bps);
+
+                // owners that never bought on the curve have no receipt and no window
+                let penalty_bps = if trader_state.data_is_empty() {
+                    0
+                } else {
+                    match Account::<TraderState>::try_from(trader_state)?
+                        .sell_penalty_bps(&ctx.accounts.config, clock.slot)
+                    {
+                        Ok(penalty_bps) => penalty_bps,
+                        Err(_) => {
+                            msg!(
+                                "{} is inside the sandwich window, skipping",
+                                curve_order.key()
+                            );
+                            continue;
+                        }
+                    }
+                };
+                penalty_lamports = bps_of(lamport_amount, penalty_bps);
+                let owner_lamports = lamport_amount - fee_lamports - penalty_lamports;
+
+                if owner_lamports < curve_order.limit_amount_out {
+                    msg!("{} is not at its limit, skipping", curve_order.key());
+                    continue;
+                }
+
+                token_launch.apply_sell(token_amount, lamport_amount)?;
+
+                let curve_order_seeds: &[&[&[u8]]] = &[&[
+                    CURVE_ORDER.as_bytes(),
+                    &token.key().to_bytes(),
+                    &owner.key().to_bytes(),
+                    &curve_order.order_id.to_le_bytes(),
+                    &[curve_order.bump],
+                ]];
+                anchor_spl::token_interface::transfer_checked(
+                    CpiContext::new_with_signer(
+                        ctx.accounts.token_program.to_account_info(),
+                        anchor_spl::token_interface::TransferChecked {
+                            from: token_account.clone(),
+                            mint: token.to_account_info(),
+                            to: ctx.accounts.launch_token_account.to_account_info(),
+                            authority: curve_order.to_account_info(),
+                        },
+                        curve_order_seeds,
+                    ),
+                    escrow_amount,
+                    token.decimals,
+                )?;
+                close_token_escrow(
+                    ctx.accounts.token_program.to_account_info(),
+                    token.to_account_info(),
+                    token_account.clone(),
+                    owner.clone(),
+                    curve_order.to_account_info(),
+                    curve_order_seeds,
+                )?;
+
+                for (to, amount) in [
+                    (owner.clone(), owner_lamports),
+                    (ctx.accounts.fee_wallet.to_account_info(), fee_lamports),
+                    (ctx.accounts.creator.to_account_info(), penalty_lamports),
+                ] {
+                    system_program::transfer(
+                        CpiContext::new_with_signer(
+                            ctx.accounts.system_program.to_account_info(),
+                            system_program::Transfer {
+                                from: ctx.accounts.sol_vault.to_account_info(),
+                                to,
+                            },
+                            sol_vault_seeds,
+                        ),
+                        amount,
+                    )?;
+                }
+
+                (
+                    lamport_amount,
+                    token_amount,
+                    fee_lamports,
+                    transfer_fee_amount,
+                )
+            }
+        };
+
+        // the tip goes to the cranker, the unspent escrow and rent back to the owner
+        curve_order.sub_lamports(curve_order.tip_lamports)?;
+        ctx.accounts
+            .cranker
+            .add_lamports(curve_order.tip_lamports)?;
+        curve_order.close(owner.clone())?;
+        ctx.accounts.price_observations.record(token_launch);
+        ctx.accounts.launch_stats.record_trade(
+            token_launch,
+            curve_order.direction,
+            lamport_amount,
+            now,
+        )?;
+        filled_orders += 1;
+
+        emit!(Trade {
+            token: token.key(),
+            user: owner.key(),
+            direction: curve_order.direction,
+            lamport_amount,
+            token_amount,
+            fee_lamports,
+            referral_fee_lamports: 0,
+            penalty_lamports,
+            transfer_fee_amount,
+            virtual_lamport_reserves: token_launch.virtual_lamport_reserves,
+            virtual_token_reserves: token_launch.virtual_token_reserves,
+            real_lamport_reserves: token_launch.real_lamport_reserves,
+        });
+    }
+
+    if filled_orders == 0 {
+        msg!("no order is at its limit");
+        return Err(NothingToDo.into());
+    }
+
+    Ok(())
+}
+
+/// checks `trader_state` is the `TraderState` address of `owner`, returning its bump
+fn trader_state_bump(
+    token: &Pubkey,
+    owner: &AccountInfo,
+    trader_state: &AccountInfo,
+) -> Result<u8> {
+    let (trader_state_key, trader_state_bump) = Pubkey::find_program_address(
+        &[
+            TRADER_STATE.as_bytes(),
+            &token.to_bytes(),
+            &owner.key().to_bytes(),
+        ],
+        &crate::ID,
+    );
+    if trader_state.key() != trader_state_key {
+        msg!(&format!("expected trader state {trader_state_key}"));
+        return Err(ValueInvalid.into());
+    }
+
+    Ok(trader_state_bump)
+}
@@ -0,0 +1,33 @@
+use crate::{
+    constants::{PRICE_OBSERVATIONS, TOKEN_LAUNCH},
+    state::{PriceObservations, TokenLaunch},
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct GetTwap<'info> {
+    /// CHECK: only used to derive the launch accounts
+    token: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [TOKEN_LAUNCH.as_bytes(), &token.key().to_bytes()],
+        bump,
+    )]
+    token_launch: Box<Account<'info, TokenLaunch>>,
+
+    #[account(
+        seeds = [PRICE_OBSERVATIONS.as_bytes(), &token.key().to_bytes()],
+        bump,
+    )]
+    price_observations: Box<Account<'info, PriceObservations>>,
+}
+
+/// Returns the time-weighted average curve price over the last `window_seconds`, in Q64 lamports
+/// per token base unit, as return data. Read-only, meant to be simulated or called through CPI.
+pub fn get_twap(ctx: Context<GetTwap>, window_seconds: u32) -> Result<u128> {
+    ctx.accounts.price_observations.twap_q64(
+        &ctx.accounts.token_launch,
+        Clock::get()?.unix_timestamp,
+        window_seconds,
+    )
+}
@@ -0,0 +1,102 @@
+use crate::{constants::TOKEN_LAUNCH, errors::*, state::TokenLaunch};
+use anchor_lang::{
+    prelude::*,
+    solana_program::program::{invoke, invoke_signed},
+    system_program,
+};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_2022::spl_token_2022::extension::transfer_fee,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+#[derive(Accounts)]
+pub struct HarvestTransferFees<'info> {
+    #[account(mut)]
+    creator: Signer<'info>,
+
+    #[account(mut, mint::token_program = token_program)]
+    token: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        seeds = [TOKEN_LAUNCH.as_bytes(), &token.key().to_bytes()],
+        bump,
+        has_one = creator @ IncorrectAuthority,
+    )]
+    token_launch: Box<Account<'info, TokenLaunch>>,
+
+    #[account(
+        init_if_needed,
+        payer = creator,
+        associated_token::mint = token,
+        associated_token::authority = creator,
+        associated_token::token_program = token_program,
+    )]
+    creator_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(address = system_program::ID)]
+    system_program: Program<'info, System>,
+
+    #[account(address = anchor_spl::token_2022::ID)]
+    token_program: Interface<'info, TokenInterface>,
+
+    #[account(address = anchor_spl::associated_token::ID)]
+    associated_token_program: Program<'info, AssociatedToken>,
+}
+
+/// Collects the transfer fees withheld on the launch's token.
+///
+/// Fees withheld in the token accounts passed as writable `remaining_accounts` are first harvested
+/// to the mint, then everything withheld in the mint is sent to the creator.
+pub fn harvest_transfer_fees<'info>(
+    ctx: Context<'_, '_, '_, 'info, HarvestTransferFees<'info>>,
+) -> Result<()> {
+    let token = &ctx.accounts.token;
+    let token_launch = &ctx.accounts.token_launch;
+    let token_program = &ctx.accounts.token_program;
+
+    if token_launch.transfer_fee.is_none() {
+        msg!("launch has no transfer fee");
+        return Err(NothingToDo.into());
+    }
+
+    if !ctx.remaining_accounts.is_empty() {
+        let sources = ctx
+            .remaining_accounts
+            .iter()
+            .map(|account| account.key)
+            .collect::<Vec<_>>();
+        let account_infos = [&[token.to_account_info()], ctx.remaining_accounts].concat();
+
+        invoke(
+            &transfer_fee::instruction::harvest_withheld_tokens_to_mint(
+                &token_program.key(),
+                &token.key(),
+                &sources,
+            )?,
+            &account_infos,
+        )?;
+    }
+
+    invoke_signed(
+        &transfer_fee::instruction::withdraw_withheld_tokens_from_mint(
+            &token_program.key(),
+            &token.key(),
+            &ctx.accounts.creator_token_account.key(),
+            &token_launch.key(),
+            &[],
+        )?,
+        &[
+            token.to_account_info(),
+            ctx.accounts.creator_token_account.to_account_info(),
+            token_launch.to_account_info(),
+        ],
+        &[&[
+            TOKEN_LAUNCH.as_bytes(),
+            &token.key().to_bytes(),
+            &[ctx.bumps.token_launch],
+        ]],
+    )?;
+
+    Ok(())
+}
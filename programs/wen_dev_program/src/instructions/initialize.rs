@@ -5,19 +5,35 @@ use anchor_spl::token::Token;
 
 #[derive(Accounts, Clone)]
 pub struct ProxyInitialize<'info> {
-    /// CHECK: Safe
+    /// CHECK: Safe. The AMM program
+    #[account(address = amm_anchor::ID)]
     pub amm_program: UncheckedAccount<'info>,
     /// CHECK: Safe. The new amm Account to be create, a PDA create with seed = [program_id, openbook_market_id, b"amm_associated_seed"]
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [amm_anchor::ID.as_ref(), market.key.as_ref(), b"amm_associated_seed"],
+        bump,
+        seeds::program = amm_program,
+    )]
     pub amm: UncheckedAccount<'info>,
     /// CHECK: Safe. Amm authority, a PDA create with seed = [b"amm authority"]
-    #[account()]
+    #[account(seeds = [b"amm authority"], bump, seeds::program = amm_program)]
     pub amm_authority: UncheckedAccount<'info>,
     /// CHECK: Safe. Amm open_orders Account, a PDA create with seed = [program_id, openbook_market_id, b"open_order_associated_seed"]
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [amm_anchor::ID.as_ref(), market.key.as_ref(), b"open_order_associated_seed"],
+        bump,
+        seeds::program = amm_program,
+    )]
     pub amm_open_orders: UncheckedAccount<'info>,
     /// CHECK: Safe. Pool lp mint account. Must be empty, owned by $authority.
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [amm_anchor::ID.as_ref(), market.key.as_ref(), b"lp_mint_associated_seed"],
+        bump,
+        seeds::program = amm_program,
+    )]
     pub amm_lp_mint: UncheckedAccount<'info>,
     /// CHECK: Safe. Coin mint account
     #[account(
@@ -30,13 +46,28 @@ pub struct ProxyInitialize<'info> {
     )]
     pub amm_pc_mint: UncheckedAccount<'info>,
     /// CHECK: Safe. amm_coin_vault Account. Must be non zero, owned by $authority
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [amm_anchor::ID.as_ref(), market.key.as_ref(), b"coin_vault_associated_seed"],
+        bump,
+        seeds::program = amm_program,
+    )]
     pub amm_coin_vault: UncheckedAccount<'info>,
     /// CHECK: Safe. amm_pc_vault Account. Must be non zero, owned by $authority.
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [amm_anchor::ID.as_ref(), market.key.as_ref(), b"pc_vault_associated_seed"],
+        bump,
+        seeds::program = amm_program,
+    )]
     pub amm_pc_vault: UncheckedAccount<'info>,
     /// CHECK: Safe. amm_target_orders Account. Must be non zero, owned by $authority.
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [amm_anchor::ID.as_ref(), market.key.as_ref(), b"target_associated_seed"],
+        bump,
+        seeds::program = amm_program,
+    )]
     pub amm_target_orders: UncheckedAccount<'info>,
     /// CHECK: Safe. Amm Config.
     #[account()]
@@ -1,17 +1,39 @@
 use crate::{
-    constants::{CONFIG, METADATA, SNIPE_QUEUE, TOKEN_LAUNCH},
+    constants::{
+        BPS_DENOMINATOR, CONFIG, CREATOR_INDEX, LAUNCH_REGISTRY, LAUNCH_STATS, METADATA,
+        NATIVE_MINT, PRICE_OBSERVATIONS, SNIPE_QUEUE, SOL_VAULT, TOKEN_LAUNCH,
+    },
     errors::*,
-    state::{Config, LaunchPhase, SnipeQueue, TokenLaunch},
+    events::Trade,
+    state::{
+        AllocationMode, Config, CreatorIndex, CreatorIndexPage, CurveKind, LaunchPhase,
+        LaunchRegistry, LaunchStats, LaunchTransferFee, MaxWalletLimit, PriceObservations,
+        SnipeQueue, SwapDirection, TokenLaunch, TokenStandard, VestingConfig,
+    },
+    utils,
+};
+use anchor_lang::{
+    prelude::*,
+    solana_program::sysvar::SysvarId,
+    solana_program::{program::invoke, program::invoke_signed, program_pack::Pack},
+    system_program,
 };
-use anchor_lang::{prelude::*, solana_program::sysvar::SysvarId, system_program};
 use anchor_spl::{
-    associated_token::AssociatedToken,
-    metadata::{mpl_token_metadata::types::DataV2, Metadata},
-    token::{spl_token::instruction::AuthorityType, Mint, Token},
+    associated_token::{get_associated_token_address_with_program_id, AssociatedToken},
+    metadata::{
+        mpl_token_metadata::types::{Collection, Creator, DataV2},
+        Metadata,
+    },
+    token_2022::spl_token_2022::{
+        self,
+        extension::{metadata_pointer, transfer_fee, ExtensionType},
+        instruction::AuthorityType,
+    },
+    token_interface::{Mint, TokenAccount, TokenInterface},
 };
+use spl_token_metadata_interface::state::TokenMetadata;
 
 #[derive(Accounts)]
-#[instruction(decimals: u8)]
 pub struct Launch<'info> {
     #[account(
         mut,
@@ -23,15 +45,11 @@ pub struct Launch<'info> {
     #[account(mut)]
     creator: Signer<'info>,
 
-    #[account(
-        init,
-        payer = creator,
-        mint::decimals = decimals,
-        mint::authority = token_launch.key(),
-    )]
-    token: Box<Account<'info, Mint>>,
+    /// CHECK: mint created in instruction, owned by `token_program`
+    #[account(mut)]
+    token: Signer<'info>,
 
-    /// CHECK: passed to token metadata program
+    /// CHECK: passed to token metadata program, only for `TokenStandard::SplToken`
     #[account(
         mut,
         seeds = [
@@ -42,14 +60,14 @@ pub struct Launch<'info> {
         bump,
         seeds::program = anchor_spl::metadata::ID
     )]
-    token_metadata_account: UncheckedAccount<'info>,
+    token_metadata_account: Option<UncheckedAccount<'info>>,
 
     /// CHECK: created in instruction
     #[account(
         mut,
         seeds = [
             &token_launch.key().to_bytes(),
-            &anchor_spl::associated_token::ID.to_bytes(),
+            &token_program.key().to_bytes(),
             &token.key().to_bytes(),
         ],
         bump,
@@ -57,6 +75,19 @@ pub struct Launch<'info> {
     )]
     launch_token_account: UncheckedAccount<'info>,
 
+    /// CHECK: created in instruction, only needed with `dev_buy_lamports`
+    #[account(
+        mut,
+        seeds = [
+            &creator.key().to_bytes(),
+            &token_program.key().to_bytes(),
+            &token.key().to_bytes(),
+        ],
+        bump,
+        seeds::program = anchor_spl::associated_token::ID
+    )]
+    creator_token_account: Option<UncheckedAccount<'info>>,
+
     #[account(
         init,
         payer = creator,
@@ -75,22 +106,114 @@ pub struct Launch<'info> {
     )]
     snipe_queue: Box<Account<'info, SnipeQueue>>,
 
+    #[account(
+        init,
+        payer = creator,
+        space = PriceObservations::ACCOUNT_LEN,
+        seeds = [PRICE_OBSERVATIONS.as_bytes(), &token.key().to_bytes()],
+        bump,
+    )]
+    price_observations: Box<Account<'info, PriceObservations>>,
+
+    #[account(
+        init,
+        payer = creator,
+        space = LaunchStats::ACCOUNT_LEN,
+        seeds = [LAUNCH_STATS.as_bytes(), &token.key().to_bytes()],
+        bump,
+    )]
+    launch_stats: Box<Account<'info, LaunchStats>>,
+
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = LaunchRegistry::ACCOUNT_LEN,
+        seeds = [LAUNCH_REGISTRY.as_bytes()],
+        bump,
+    )]
+    launch_registry: Box<Account<'info, LaunchRegistry>>,
+
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = CreatorIndex::ACCOUNT_LEN,
+        seeds = [CREATOR_INDEX.as_bytes(), &creator.key().to_bytes()],
+        bump,
+    )]
+    creator_index: Box<Account<'info, CreatorIndex>>,
+
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = CreatorIndexPage::ACCOUNT_LEN,
+        seeds = [
+            CREATOR_INDEX.as_bytes(),
+            &creator.key().to_bytes(),
+            &creator_index.current_page().to_le_bytes(),
+        ],
+        bump,
+    )]
+    creator_index_page: Box<Account<'info, CreatorIndexPage>>,
+
+    /// CHECK: lamport-only PDA holding the bonding curve reserves, funded to rent exemption here
+    #[account(
+        mut,
+        seeds = [SOL_VAULT.as_bytes(), &token.key().to_bytes()],
+        bump,
+    )]
+    sol_vault: UncheckedAccount<'info>,
+
+    /// only for launches priced in an SPL token
+    #[account(mint::token_program = quote_token_program)]
+    quote_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    /// CHECK: created in instruction, holds the curve reserves of SPL-quoted launches
+    #[account(mut)]
+    quote_vault: Option<UncheckedAccount<'info>>,
+
+    /// only for dev buys of SPL-quoted launches
+    #[account(
+        mut,
+        token::mint = quote_mint,
+        token::authority = creator,
+        token::token_program = quote_token_program,
+    )]
+    creator_quote_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    quote_token_program: Option<Interface<'info, TokenInterface>>,
+
     #[account(address = system_program::ID)]
     system_program: Program<'info, System>,
 
     #[account(address = Rent::id())]
     rent: Sysvar<'info, Rent>,
 
-    #[account(address = anchor_spl::token::ID)]
-    token_program: Program<'info, Token>,
+    token_program: Interface<'info, TokenInterface>,
 
     #[account(address = anchor_spl::associated_token::ID)]
     associated_token_program: Program<'info, AssociatedToken>,
 
+    /// only for `TokenStandard::SplToken`
     #[account(address = anchor_spl::metadata::ID)]
-    mpl_token_metadata_program: Program<'info, Metadata>,
+    mpl_token_metadata_program: Option<Program<'info, Metadata>>,
+
+    /// CHECK: `Config.collection_mint`, only for `TokenStandard::SplToken` launches joining the
+    /// platform collection
+    collection_mint: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: checked by the token metadata program
+    #[account(mut)]
+    collection_metadata: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: checked by the token metadata program
+    collection_master_edition: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: collection authority record delegating the platform collection to `config`, checked
+    /// by the token metadata program
+    collection_authority_record: Option<UncheckedAccount<'info>>,
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn launch<'info>(
     ctx: Context<'_, '_, '_, 'info, Launch<'info>>,
 
@@ -98,11 +221,28 @@ pub fn launch<'info>(
     name: String,
     symbol: String,
     uri: String,
+    verify_creator: bool,
 
     // launch config
+    token_standard: TokenStandard,
+    transfer_fee: Option<LaunchTransferFee>,
+    curve_kind: CurveKind,
     virtual_lamport_reserves: u64,
     token_supply: u64,
     decimals: u8,
+
+    // presale config
+    allocation_mode: AllocationMode,
+    presale_token_cap: u64,
+    vesting: Option<VestingConfig>,
+    presale_start: i64,
+    presale_end: i64,
+
+    // creator buy
+    dev_buy_lamports: Option<u64>,
+
+    // anti-whale
+    max_wallet: Option<MaxWalletLimit>,
 ) -> Result<()> {
     let config = &ctx.accounts.config;
     let creator = &ctx.accounts.creator;
@@ -113,6 +253,28 @@ pub fn launch<'info>(
 
     // TODO: verify checks
 
+    if ctx.accounts.token_program.key() != token_standard.program_id() {
+        msg!(
+            "{token_standard:?} launches must use token program {}",
+            token_standard.program_id()
+        );
+        return Err(IncorrectTokenProgram.into());
+    }
+    utils::validate_metadata(&name, &symbol, &uri)?;
+    if verify_creator && token_standard != TokenStandard::SplToken {
+        msg!("verified creators are only available to SplToken launches");
+        return Err(ValueInvalid.into());
+    }
+    if let Some(transfer_fee) = &transfer_fee {
+        if token_standard != TokenStandard::Token2022 {
+            msg!("transfer fees are only available to Token2022 launches");
+            return Err(ValueInvalid.into());
+        }
+        config
+            .transfer_fee_bps_config
+            .validate(&transfer_fee.basis_points)?;
+    }
+
     let decimal_multiplier = 10u64.pow(decimals as u32);
     let fractional_tokens = token_supply % decimal_multiplier;
     if fractional_tokens != 0 {
@@ -120,32 +282,266 @@ pub fn launch<'info>(
         return Err(ValueInvalid.into());
     }
 
-    config
-        .lamport_amount_config
-        .validate(&virtual_lamport_reserves)?;
+    config.curve_kind_config.validate(&curve_kind)?;
+    curve_kind.validate()?;
+
+    let quote_mint = ctx
+        .accounts
+        .quote_mint
+        .as_ref()
+        .map_or(NATIVE_MINT, |quote_mint| quote_mint.key());
+    let (graduation_lamports, virtual_reserves_config) = config.quote_limits(&quote_mint)?;
+    virtual_reserves_config.validate(&virtual_lamport_reserves)?;
 
     config
         .token_supply_config
         .validate(&(token_supply / decimal_multiplier))?;
     config.token_decimals_config.validate(&decimals)?;
 
+    if presale_token_cap >= token_supply {
+        msg!(&format!(
+            "presale_token_cap {presale_token_cap} must be below token_supply {token_supply}"
+        ));
+        return Err(ValueTooLarge.into());
+    }
+    if let Some(vesting) = &vesting {
+        vesting.validate()?;
+    }
+    if let Some(max_wallet) = &max_wallet {
+        max_wallet.validate(config)?;
+    }
+
+    let now = Clock::get()?.unix_timestamp;
+    if presale_end <= presale_start {
+        msg!(&format!(
+            "presale_end {presale_end} must be after presale_start {presale_start}"
+        ));
+        return Err(ValueInvalid.into());
+    }
+    config
+        .presale_delay_config
+        .validate(&presale_start.saturating_sub(now))?;
+    config
+        .presale_duration_config
+        .validate(&presale_end.saturating_sub(presale_start))?;
+
+    // register launch
+    let launch_registry = &mut ctx.accounts.launch_registry;
+    let creator_index = &mut ctx.accounts.creator_index;
+    let creator_index_page = &mut ctx.accounts.creator_index_page;
+
+    if config.max_presales_per_creator > 0
+        && creator_index.active_presales >= config.max_presales_per_creator
+    {
+        msg!(
+            "{} already has {} launches in presale",
+            creator.key(),
+            creator_index.active_presales
+        );
+        return Err(PresaleLimitReached.into());
+    }
+
+    creator_index_page.creator = creator.key();
+    creator_index_page.page = creator_index.current_page();
+    creator_index_page.tokens.push(token.key());
+
+    creator_index.creator = creator.key();
+    creator_index.launch_count += 1;
+    creator_index.active_presales += 1;
+
+    token_launch.launch_id = launch_registry.launch_count;
+    launch_registry.launch_count += 1;
+
     // create launch
     token_launch.token = token.key();
+    token_launch.token_standard = token_standard;
+    token_launch.transfer_fee = transfer_fee;
+    token_launch.curve_kind = curve_kind;
+    token_launch.quote_mint = quote_mint;
     token_launch.creator = creator.key();
     token_launch.phase = LaunchPhase::Presale;
+    token_launch.created_at = now;
+    token_launch.presale_start = presale_start;
+    token_launch.presale_end = presale_end;
     token_launch.virtual_lamport_reserves = virtual_lamport_reserves;
     token_launch.virtual_token_reserves = token_supply;
     token_launch.initial_token_max_supply = token_supply;
+    token_launch.initial_virtual_lamport_reserves = virtual_lamport_reserves;
+    token_launch.real_lamport_reserves = 0;
+    token_launch.graduation_lamports = graduation_lamports;
+    token_launch.allocation_mode = allocation_mode;
+    token_launch.presale_token_cap = presale_token_cap;
+    token_launch.vesting = vesting;
+    token_launch.max_wallet = max_wallet;
+    token_launch.price_updated_at = now;
+
+    // quote the creator buy against the starting reserves
+    let dev_buy_lamports = dev_buy_lamports.unwrap_or(0);
+    let dev_buy_token_amount = token_launch.quote_buy(dev_buy_lamports)?;
+    if dev_buy_lamports > 0 {
+        let max_token_amount = ((token_supply as u128) * (config.dev_buy_max_bps as u128)
+            / (BPS_DENOMINATOR as u128)) as u64;
+        if dev_buy_token_amount > max_token_amount {
+            msg!(&format!("dev buy of {dev_buy_token_amount} tokens exceeds the maximum of {max_token_amount}"));
+            return Err(ValueTooLarge.into());
+        }
+        if dev_buy_lamports >= token_launch.lamports_to_graduation() {
+            msg!(&format!(
+                "dev buy of {dev_buy_lamports} lamports would complete the curve"
+            ));
+            return Err(ValueTooLarge.into());
+        }
+
+        token_launch.apply_buy(dev_buy_lamports, dev_buy_token_amount)?;
+    }
+    token_launch.dev_buy_lamports = dev_buy_lamports;
+    token_launch.dev_buy_token_amount = dev_buy_token_amount;
 
     snipe_queue.token = token_launch.token;
 
+    let price_observations = &mut ctx.accounts.price_observations;
+    price_observations.token = token_launch.token;
+    price_observations.record(token_launch);
+
+    let launch_stats = &mut ctx.accounts.launch_stats;
+    launch_stats.token = token_launch.token;
+    if dev_buy_lamports > 0 {
+        launch_stats.record_trade(token_launch, SwapDirection::Buy, dev_buy_lamports, now)?;
+    }
+
+    let token_launch_seeds: &[&[&[u8]]] = &[&[
+        TOKEN_LAUNCH.as_bytes(),
+        &token.key().to_bytes(),
+        &[ctx.bumps.token_launch],
+    ]];
+
+    // fund the sol vault so it stays rent exempt however far the curve is sold down
+    system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: creator.to_account_info(),
+                to: ctx.accounts.sol_vault.to_account_info(),
+            },
+        ),
+        Rent::get()?.minimum_balance(0),
+    )?;
+
+    // SPL-quoted curves keep their reserves in the launch's token account for the quote mint
+    if !token_launch.is_native_quote() {
+        let (Some(quote_mint), Some(quote_vault), Some(quote_token_program)) = (
+            &ctx.accounts.quote_mint,
+            &ctx.accounts.quote_vault,
+            &ctx.accounts.quote_token_program,
+        ) else {
+            msg!("SPL-quoted launches need the quote vault and quote token program");
+            return Err(MissingAccount.into());
+        };
+        let expected_quote_vault = get_associated_token_address_with_program_id(
+            &token_launch.key(),
+            &quote_mint.key(),
+            &quote_token_program.key(),
+        );
+        if quote_vault.key() != expected_quote_vault {
+            msg!(&format!("expected quote vault {expected_quote_vault}"));
+            return Err(IncorrectTokenAccount.into());
+        }
+
+        anchor_spl::associated_token::create(CpiContext::new(
+            ctx.accounts.associated_token_program.to_account_info(),
+            anchor_spl::associated_token::Create {
+                payer: creator.to_account_info(),
+                associated_token: quote_vault.to_account_info(),
+                authority: token_launch.to_account_info(),
+                mint: quote_mint.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                token_program: quote_token_program.to_account_info(),
+            },
+        ))?;
+    }
+
+    // create mint, Token-2022 mints point their metadata at themselves
+    let (mint_len, metadata_len) = match token_standard {
+        TokenStandard::SplToken => (spl_token_2022::state::Mint::LEN, 0),
+        TokenStandard::Token2022 => {
+            let mut extensions = vec![ExtensionType::MetadataPointer];
+            if transfer_fee.is_some() {
+                extensions.push(ExtensionType::TransferFeeConfig);
+            }
+            let mint_len = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(
+                &extensions,
+            )?;
+            let metadata_len = TokenMetadata {
+                name: name.clone(),
+                symbol: symbol.clone(),
+                uri: uri.clone(),
+                ..Default::default()
+            }
+            .tlv_size_of()?;
+            (mint_len, metadata_len)
+        }
+    };
+
+    system_program::create_account(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::CreateAccount {
+                from: creator.to_account_info(),
+                to: token.to_account_info(),
+            },
+        ),
+        // token-metadata reallocs the mint on initialization, so fund that up front
+        Rent::get()?.minimum_balance(mint_len + metadata_len),
+        mint_len as u64,
+        &ctx.accounts.token_program.key(),
+    )?;
+
+    if token_standard == TokenStandard::Token2022 {
+        invoke(
+            &metadata_pointer::instruction::initialize(
+                &ctx.accounts.token_program.key(),
+                &token.key(),
+                Some(token_launch.key()),
+                Some(token.key()),
+            )?,
+            &[token.to_account_info()],
+        )?;
+    }
+
+    // the launch PDA can withdraw withheld fees on behalf of the creator, nobody can change the fee
+    if let Some(transfer_fee) = &transfer_fee {
+        invoke(
+            &transfer_fee::instruction::initialize_transfer_fee_config(
+                &ctx.accounts.token_program.key(),
+                &token.key(),
+                None,
+                Some(&token_launch.key()),
+                transfer_fee.basis_points,
+                transfer_fee.maximum_fee,
+            )?,
+            &[token.to_account_info()],
+        )?;
+    }
+
+    anchor_spl::token_interface::initialize_mint2(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            anchor_spl::token_interface::InitializeMint2 {
+                mint: token.to_account_info(),
+            },
+        ),
+        decimals,
+        &token_launch.key(),
+        None,
+    )?;
+
     // create launch token account
     anchor_spl::associated_token::create(CpiContext::new(
         ctx.accounts.associated_token_program.to_account_info(),
         anchor_spl::associated_token::Create {
             payer: creator.to_account_info(),
             associated_token: launch_token_account.to_account_info(),
-            authority: launch_token_account.to_account_info(),
+            authority: token_launch.to_account_info(),
             mint: token.to_account_info(),
             system_program: ctx.accounts.system_program.to_account_info(),
             token_program: ctx.accounts.token_program.to_account_info(),
@@ -153,69 +549,239 @@ pub fn launch<'info>(
     ))?;
 
     // mint tokens
-    anchor_spl::token::mint_to(
+    anchor_spl::token_interface::mint_to(
         CpiContext::new_with_signer(
             ctx.accounts.token_program.to_account_info(),
-            anchor_spl::token::MintTo {
+            anchor_spl::token_interface::MintTo {
                 mint: token.to_account_info(),
                 to: launch_token_account.to_account_info(),
-                authority: launch_token_account.to_account_info(),
+                authority: token_launch.to_account_info(),
             },
-            &[&[
-                TOKEN_LAUNCH.as_bytes(),
-                &token.key().to_bytes(),
-                &[ctx.bumps.token_launch],
-            ]],
+            token_launch_seeds,
         ),
         token_supply,
     )?;
 
     // create metadata
-    anchor_spl::metadata::create_metadata_accounts_v3(
-        CpiContext::new_with_signer(
-            ctx.accounts.mpl_token_metadata_program.to_account_info(),
-            anchor_spl::metadata::CreateMetadataAccountsV3 {
-                metadata: ctx.accounts.token_metadata_account.to_account_info(),
-                mint: token.to_account_info(),
-                mint_authority: launch_token_account.to_account_info(),
+    match token_standard {
+        TokenStandard::SplToken => {
+            let (Some(token_metadata_account), Some(mpl_token_metadata_program)) = (
+                &ctx.accounts.token_metadata_account,
+                &ctx.accounts.mpl_token_metadata_program,
+            ) else {
+                msg!("SplToken launches need the token metadata account and program");
+                return Err(MissingAccount.into());
+            };
+
+            anchor_spl::metadata::create_metadata_accounts_v3(
+                CpiContext::new_with_signer(
+                    mpl_token_metadata_program.to_account_info(),
+                    anchor_spl::metadata::CreateMetadataAccountsV3 {
+                        metadata: token_metadata_account.to_account_info(),
+                        mint: token.to_account_info(),
+                        mint_authority: token_launch.to_account_info(),
+                        payer: creator.to_account_info(),
+                        update_authority: token_launch.to_account_info(),
+                        system_program: ctx.accounts.system_program.to_account_info(),
+                        rent: ctx.accounts.rent.to_account_info(),
+                    },
+                    token_launch_seeds,
+                ),
+                DataV2 {
+                    name,
+                    symbol,
+                    uri,
+                    seller_fee_basis_points: 0,
+                    creators: verify_creator.then(|| {
+                        vec![Creator {
+                            address: creator.key(),
+                            verified: false,
+                            share: 100,
+                        }]
+                    }),
+                    collection: config.collection_mint.map(|key| Collection {
+                        verified: false,
+                        key,
+                    }),
+                    uses: None,
+                },
+                false,
+                true,
+                None,
+            )?;
+
+            // creators can only verify themselves, the creator signs the launch
+            if verify_creator {
+                anchor_spl::metadata::sign_metadata(CpiContext::new(
+                    mpl_token_metadata_program.to_account_info(),
+                    anchor_spl::metadata::SignMetadata {
+                        creator: creator.to_account_info(),
+                        metadata: token_metadata_account.to_account_info(),
+                    },
+                ))?;
+            }
+
+            // the config PDA verifies the launch as a member of the platform collection
+            if let Some(collection_mint_key) = config.collection_mint {
+                let (
+                    Some(collection_mint),
+                    Some(collection_metadata),
+                    Some(collection_master_edition),
+                    Some(collection_authority_record),
+                ) = (
+                    &ctx.accounts.collection_mint,
+                    &ctx.accounts.collection_metadata,
+                    &ctx.accounts.collection_master_edition,
+                    &ctx.accounts.collection_authority_record,
+                )
+                else {
+                    msg!("SplToken launches need the platform collection accounts");
+                    return Err(MissingAccount.into());
+                };
+                if collection_mint.key() != collection_mint_key {
+                    msg!(&format!("expected collection mint {collection_mint_key}"));
+                    return Err(ValueInvalid.into());
+                }
+
+                anchor_spl::metadata::verify_sized_collection_item(
+                    CpiContext::new_with_signer(
+                        mpl_token_metadata_program.to_account_info(),
+                        anchor_spl::metadata::VerifySizedCollectionItem {
+                            payer: creator.to_account_info(),
+                            metadata: token_metadata_account.to_account_info(),
+                            collection_authority: config.to_account_info(),
+                            collection_mint: collection_mint.to_account_info(),
+                            collection_metadata: collection_metadata.to_account_info(),
+                            collection_master_edition: collection_master_edition.to_account_info(),
+                        },
+                        &[&[CONFIG.as_bytes(), &[ctx.bumps.config]]],
+                    )
+                    .with_remaining_accounts(vec![collection_authority_record.to_account_info()]),
+                    Some(collection_authority_record.key()),
+                )?;
+            }
+        }
+        TokenStandard::Token2022 => {
+            invoke_signed(
+                &spl_token_metadata_interface::instruction::initialize(
+                    &ctx.accounts.token_program.key(),
+                    &token.key(),
+                    &token_launch.key(),
+                    &token.key(),
+                    &token_launch.key(),
+                    name,
+                    symbol,
+                    uri,
+                ),
+                &[
+                    token.to_account_info(),
+                    token_launch.to_account_info(),
+                    token.to_account_info(),
+                    token_launch.to_account_info(),
+                ],
+                token_launch_seeds,
+            )?;
+        }
+    }
+
+    // deliver the creator buy
+    if dev_buy_lamports > 0 {
+        let Some(creator_token_account) = &ctx.accounts.creator_token_account else {
+            msg!("dev buys need the creator token account");
+            return Err(MissingAccount.into());
+        };
+
+        match (
+            &ctx.accounts.quote_mint,
+            &ctx.accounts.quote_vault,
+            &ctx.accounts.creator_quote_account,
+            &ctx.accounts.quote_token_program,
+        ) {
+            _ if token_launch.is_native_quote() => system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: creator.to_account_info(),
+                        to: ctx.accounts.sol_vault.to_account_info(),
+                    },
+                ),
+                dev_buy_lamports,
+            )?,
+            (
+                Some(quote_mint),
+                Some(quote_vault),
+                Some(creator_quote_account),
+                Some(quote_token_program),
+            ) => utils::transfer_quote(
+                quote_token_program,
+                quote_mint,
+                creator_quote_account.to_account_info(),
+                quote_vault.to_account_info(),
+                creator.to_account_info(),
+                &[],
+                dev_buy_lamports,
+            )?,
+            _ => {
+                msg!("dev buys of SPL-quoted launches need the creator quote account");
+                return Err(MissingAccount.into());
+            }
+        }
+
+        anchor_spl::associated_token::create(CpiContext::new(
+            ctx.accounts.associated_token_program.to_account_info(),
+            anchor_spl::associated_token::Create {
                 payer: creator.to_account_info(),
-                update_authority: launch_token_account.to_account_info(),
+                associated_token: creator_token_account.to_account_info(),
+                authority: creator.to_account_info(),
+                mint: token.to_account_info(),
                 system_program: ctx.accounts.system_program.to_account_info(),
-                rent: ctx.accounts.rent.to_account_info(),
+                token_program: ctx.accounts.token_program.to_account_info(),
             },
-            &[&[
-                TOKEN_LAUNCH.as_bytes(),
-                &token.key().to_bytes(),
-                &[ctx.bumps.token_launch],
-            ]],
-        ),
-        DataV2 {
-            name,
-            symbol,
-            uri,
-            seller_fee_basis_points: 0,
-            creators: None,
-            collection: None,
-            uses: None,
-        },
-        false,
-        true,
-        None,
-    )?;
+        ))?;
+
+        anchor_spl::token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                anchor_spl::token_interface::TransferChecked {
+                    from: launch_token_account.to_account_info(),
+                    mint: token.to_account_info(),
+                    to: creator_token_account.to_account_info(),
+                    authority: token_launch.to_account_info(),
+                },
+                token_launch_seeds,
+            ),
+            dev_buy_token_amount,
+            decimals,
+        )?;
+
+        emit!(Trade {
+            token: token.key(),
+            user: creator.key(),
+            direction: SwapDirection::Buy,
+            lamport_amount: dev_buy_lamports,
+            token_amount: dev_buy_token_amount,
+            fee_lamports: 0,
+            referral_fee_lamports: 0,
+            penalty_lamports: 0,
+            transfer_fee_amount: utils::transfer_fee(
+                &token.to_account_info(),
+                dev_buy_token_amount
+            )?,
+            virtual_lamport_reserves: token_launch.virtual_lamport_reserves,
+            virtual_token_reserves: token_launch.virtual_token_reserves,
+            real_lamport_reserves: token_launch.real_lamport_reserves,
+        });
+    }
 
     // relinquish mint authority
-    anchor_spl::token::set_authority(
+    anchor_spl::token_interface::set_authority(
         CpiContext::new_with_signer(
             ctx.accounts.token_program.to_account_info(),
-            anchor_spl::token::SetAuthority {
-                current_authority: launch_token_account.to_account_info(),
+            anchor_spl::token_interface::SetAuthority {
+                current_authority: token_launch.to_account_info(),
                 account_or_mint: token.to_account_info(),
             },
-            &[&[
-                TOKEN_LAUNCH.as_bytes(),
-                &token.key().to_bytes(),
-                &[ctx.bumps.token_launch],
-            ]],
+            token_launch_seeds,
         ),
         AuthorityType::MintTokens,
         None,
@@ -1,11 +1,15 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::{self, TokenAccount, Transfer, Token, Mint}
+    metadata::Metadata,
+    token_2022::spl_token_2022::instruction::AuthorityType,
+    token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked},
 };
+use spl_pod::optional_keys::OptionalNonZeroPubkey;
 use solana_program::{program::invoke_signed, system_instruction};
 
 use crate::constants::{
+    METADATA,
     SOL_VAULT,
     TOKEN_LAUNCH,
     CONFIG
@@ -14,9 +18,12 @@ use crate::constants::{
 use crate::state::{
     TokenLaunch,
     LaunchPhase,
-    Config
+    Config,
+    MetadataMigrationPolicy,
+    TokenStandard
 };
 use crate::errors::*;
+use crate::utils::transfer_quote;
 
 #[derive(Accounts)]
 pub struct Migrate<'info> {
@@ -32,21 +39,21 @@ pub struct Migrate<'info> {
     )]
     config: Account<'info, Config>,
 
-    #[account(mut)]
-    token: Box<Account<'info, Mint>>,
+    #[account(mut, mint::token_program = token_program)]
+    token: Box<InterfaceAccount<'info, Mint>>,
 
     /// CHECK: created in instruction
     #[account(
         mut,
         seeds = [
             &token_launch.key().to_bytes(),
-            &anchor_spl::associated_token::ID.to_bytes(),
+            &token_program.key().to_bytes(),
             &token.key().to_bytes(),
         ],
         bump,
         seeds::program = anchor_spl::associated_token::ID
     )]
-    launch_token_account: Account<'info, TokenAccount>,
+    launch_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         mut,
@@ -70,16 +77,60 @@ pub struct Migrate<'info> {
         payer = authority,
         associated_token::mint = token,
         associated_token::authority = special_sol_wallet,
+        associated_token::token_program = token_program,
     )]
-    pub special_token_wallet: Account<'info, TokenAccount>,
+    pub special_token_wallet: Box<InterfaceAccount<'info, TokenAccount>>,
 
     // Special wallet to receive the withdrawn SOL
     /// CHECK:` doc comment explaining why no checks through types are necessary.
     #[account(mut, constraint = special_sol_wallet.key() == config.fee_wallet)]
     pub special_sol_wallet: AccountInfo<'info>,
 
-    // Token program account
-    pub token_program: Program<'info, Token>,
+    // Quote mint of SPL-quoted launches, becomes the pc side of the pool
+    #[account(address = token_launch.quote_mint, mint::token_program = quote_token_program)]
+    pub quote_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    // The account holding the quote tokens from the bonding curve of SPL-quoted launches
+    #[account(
+        mut,
+        associated_token::mint = quote_mint,
+        associated_token::authority = token_launch,
+        associated_token::token_program = quote_token_program,
+    )]
+    pub quote_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    // Special wallet to receive the withdrawn quote tokens
+    #[account(
+        mut,
+        associated_token::mint = quote_mint,
+        associated_token::authority = special_sol_wallet,
+        associated_token::token_program = quote_token_program,
+    )]
+    pub special_quote_wallet: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    // Metaplex metadata of SplToken launches, frozen or handed to the creator per Config
+    /// CHECK: passed to token metadata program
+    #[account(
+        mut,
+        seeds = [
+            METADATA.as_bytes(),
+            &anchor_spl::metadata::ID.to_bytes(),
+            &token.key().to_bytes(),
+        ],
+        bump,
+        seeds::program = anchor_spl::metadata::ID
+    )]
+    pub token_metadata_account: Option<UncheckedAccount<'info>>,
+
+    // Token metadata program account, only for SplToken launches
+    #[account(address = anchor_spl::metadata::ID)]
+    pub mpl_token_metadata_program: Option<Program<'info, Metadata>>,
+
+    // Quote token program account, only for SPL-quoted launches
+    pub quote_token_program: Option<Interface<'info, TokenInterface>>,
+
+    // Token program account, spl-token or Token-2022
+    pub token_program: Interface<'info, TokenInterface>,
     // AssociatedToken program account
     pub associated_token_program: Program<'info, AssociatedToken>,
     // System program account (for transferring SOL)
@@ -98,19 +149,44 @@ pub fn migrate(ctx: Context<Migrate>) -> Result<()> {
 
     let token_balance = accts.launch_token_account.amount;
 
-    token::transfer(
+    token_interface::transfer_checked(
         CpiContext::new_with_signer(
             accts.token_program.to_account_info(),
-            Transfer {
+            TransferChecked {
                 from: accts.launch_token_account.to_account_info(),
+                mint: accts.token.to_account_info(),
                 to: accts.special_token_wallet.to_account_info(),
-                authority: accts.authority.to_account_info(),
+                authority: accts.token_launch.to_account_info(),
             },
             signer,
         ),
-        token_balance
+        token_balance,
+        accts.token.decimals,
     )?;
 
+    // SPL-quoted launches hand over the quote tokens, the pool is then initialized with the quote mint as pc
+    if !accts.token_launch.is_native_quote() {
+        let (Some(quote_mint), Some(quote_vault), Some(special_quote_wallet), Some(quote_token_program)) = (
+            &accts.quote_mint,
+            &accts.quote_vault,
+            &accts.special_quote_wallet,
+            &accts.quote_token_program,
+        ) else {
+            msg!("SPL-quoted launches need the quote vault and special quote wallet");
+            return Err(MissingAccount.into());
+        };
+
+        transfer_quote(
+            quote_token_program,
+            quote_mint,
+            quote_vault.to_account_info(),
+            special_quote_wallet.to_account_info(),
+            accts.token_launch.to_account_info(),
+            signer,
+            quote_vault.amount,
+        )?;
+    }
+
     // 2. Transfer SOL to special wallet
     let (_, sol_bump) = Pubkey::find_program_address(&[SOL_VAULT.as_bytes(), &accts.token.key().to_bytes()], ctx.program_id);
     let sol_vault_seeds = &[SOL_VAULT.as_bytes(), &accts.token.key().to_bytes(), &[sol_bump]];
@@ -127,5 +203,63 @@ pub fn migrate(ctx: Context<Migrate>) -> Result<()> {
         sol_signer,
     )?;
 
+    // 3. Freeze the metadata or hand it to the creator
+    let new_update_authority = match accts.config.metadata_migration_policy {
+        MetadataMigrationPolicy::Freeze => None,
+        MetadataMigrationPolicy::HandToCreator => Some(accts.token_launch.creator),
+    };
+    match accts.token_launch.token_standard {
+        TokenStandard::SplToken => {
+            let (Some(token_metadata_account), Some(mpl_token_metadata_program)) = (
+                &accts.token_metadata_account,
+                &accts.mpl_token_metadata_program,
+            ) else {
+                msg!("SplToken launches need the token metadata account and program");
+                return Err(MissingAccount.into());
+            };
+
+            anchor_spl::metadata::update_metadata_accounts_v2(
+                CpiContext::new_with_signer(
+                    mpl_token_metadata_program.to_account_info(),
+                    anchor_spl::metadata::UpdateMetadataAccountsV2 {
+                        metadata: token_metadata_account.to_account_info(),
+                        update_authority: accts.token_launch.to_account_info(),
+                    },
+                    signer,
+                ),
+                new_update_authority,
+                None,
+                None,
+                Some(new_update_authority.is_some()),
+            )?;
+        }
+        TokenStandard::Token2022 => {
+            invoke_signed(
+                &spl_token_metadata_interface::instruction::update_authority(
+                    &accts.token_program.key(),
+                    &accts.token.key(),
+                    &accts.token_launch.key(),
+                    OptionalNonZeroPubkey::try_from(new_update_authority)?,
+                ),
+                &[accts.token.to_account_info(), accts.token_launch.to_account_info()],
+                signer,
+            )?;
+            token_interface::set_authority(
+                CpiContext::new_with_signer(
+                    accts.token_program.to_account_info(),
+                    token_interface::SetAuthority {
+                        current_authority: accts.token_launch.to_account_info(),
+                        account_or_mint: accts.token.to_account_info(),
+                    },
+                    signer,
+                ),
+                AuthorityType::MetadataPointer,
+                new_update_authority,
+            )?;
+        }
+    }
+
+    accts.token_launch.phase = LaunchPhase::Migrated;
+
     Ok(())
 }
@@ -7,17 +7,79 @@ pub use snipe::*;
 pub mod launch;
 pub use launch::*;
 
+pub mod close_presale;
+pub use close_presale::*;
+
+pub mod process_snipes;
+pub use process_snipes::*;
+
+pub mod open_sale;
+pub use open_sale::*;
+
+pub mod swap;
+pub use swap::*;
+
+pub mod route_swap;
+pub use route_swap::*;
+
+pub mod harvest_transfer_fees;
+pub use harvest_transfer_fees::*;
+
+pub mod update_launch_metadata;
+pub use update_launch_metadata::*;
+
+pub mod claim_vested;
+pub use claim_vested::*;
+
+pub mod cancel_launch;
+pub use cancel_launch::*;
+
+pub mod expire_launch;
+pub use expire_launch::*;
+
+pub mod reclaim_snipe;
+pub use reclaim_snipe::*;
+
+pub mod reclaim_dev_buy;
+pub use reclaim_dev_buy::*;
+
+pub mod register_referrer;
+pub use register_referrer::*;
+
+pub mod claim_referral_fees;
+pub use claim_referral_fees::*;
+
+pub mod place_curve_order;
+pub use place_curve_order::*;
+
+pub mod cancel_curve_order;
+pub use cancel_curve_order::*;
+
+pub mod fill_curve_orders;
+pub use fill_curve_orders::*;
+
+pub mod get_twap;
+pub use get_twap::*;
+
 pub mod migrate;
 pub use migrate::*;
 
+pub mod close_launch;
+pub use close_launch::*;
+
+pub mod close_trader_state;
+pub use close_trader_state::*;
+
 pub mod deposit;
 pub mod initialize;
 pub mod swap_base_in;
 pub mod swap_base_out;
+pub mod route_swap_base_in;
 pub mod withdraw;
 
 pub use deposit::*;
 pub use initialize::*;
 pub use swap_base_in::*;
 pub use swap_base_out::*;
+pub use route_swap_base_in::*;
 pub use withdraw::*;
@@ -0,0 +1,53 @@
+use crate::{
+    constants::{SNIPE_QUEUE, TOKEN_LAUNCH},
+    errors::*,
+    state::{LaunchPhase, SnipeQueue, TokenLaunch},
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+#[instruction(token: Pubkey)]
+pub struct OpenSale<'info> {
+    #[account(
+        mut,
+        seeds = [TOKEN_LAUNCH.as_bytes(), &token.to_bytes()],
+        bump,
+    )]
+    token_launch: Box<Account<'info, TokenLaunch>>,
+
+    #[account(seeds = [SNIPE_QUEUE.as_bytes(), &token.to_bytes()], bump)]
+    snipe_queue: Box<Account<'info, SnipeQueue>>,
+}
+
+pub fn open_sale<'info>(
+    ctx: Context<'_, '_, '_, 'info, OpenSale<'info>>,
+    _token: Pubkey,
+) -> Result<()> {
+    let token_launch = &mut ctx.accounts.token_launch;
+    let snipe_queue = &ctx.accounts.snipe_queue;
+
+    token_launch
+        .phase
+        .assert_eq(&LaunchPhase::ProcessingPresale)?;
+
+    let unprocessed = snipe_queue
+        .snipes
+        .iter()
+        .filter(|snipe_config| !snipe_config.processed)
+        .count();
+    if unprocessed > 0 {
+        msg!(&format!("{unprocessed} snipes still need to be processed"));
+        return Err(SnipesNotProcessed.into());
+    }
+
+    // a presale that filled the curve up to graduation completes it
+    if token_launch.lamports_to_graduation() == 0 {
+        token_launch.phase = LaunchPhase::Completed;
+        return Ok(());
+    }
+
+    token_launch.phase = LaunchPhase::OpenSale;
+    token_launch.sale_opened_at = Clock::get()?.unix_timestamp;
+
+    Ok(())
+}
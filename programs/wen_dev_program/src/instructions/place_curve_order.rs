@@ -0,0 +1,168 @@
+use crate::{
+    constants::{CONFIG, CURVE_ORDER, TOKEN_LAUNCH},
+    errors::*,
+    state::{Config, CurveOrder, LaunchPhase, SwapDirection, TokenLaunch},
+    utils::{bps_of, transfer_fee},
+};
+use anchor_lang::{prelude::*, system_program};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+#[derive(Accounts)]
+#[instruction(order_id: u64)]
+pub struct PlaceCurveOrder<'info> {
+    #[account(mut)]
+    owner: Signer<'info>,
+
+    #[account(seeds = [CONFIG.as_bytes()], bump)]
+    config: Box<Account<'info, Config>>,
+
+    #[account(mint::token_program = token_program)]
+    token: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        seeds = [TOKEN_LAUNCH.as_bytes(), &token.key().to_bytes()],
+        bump,
+    )]
+    token_launch: Box<Account<'info, TokenLaunch>>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = CurveOrder::ACCOUNT_LEN,
+        seeds = [
+            CURVE_ORDER.as_bytes(),
+            &token.key().to_bytes(),
+            &owner.key().to_bytes(),
+            &order_id.to_le_bytes(),
+        ],
+        bump,
+    )]
+    curve_order: Box<Account<'info, CurveOrder>>,
+
+    /// receives the tokens of buy orders, funds sell orders
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = token,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program,
+    )]
+    owner_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// escrow of sell orders
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = token,
+        associated_token::authority = curve_order,
+        associated_token::token_program = token_program,
+    )]
+    order_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    #[account(address = system_program::ID)]
+    system_program: Program<'info, System>,
+
+    token_program: Interface<'info, TokenInterface>,
+
+    #[account(address = anchor_spl::associated_token::ID)]
+    associated_token_program: Program<'info, AssociatedToken>,
+}
+
+/// Places a resting order against the bonding curve, escrowing what it trades.
+///
+/// Buys escrow `amount_in` lamports plus the current platform fee, sells escrow `amount_in` tokens.
+/// Both escrow `Config.curve_order_tip_lamports` for the cranker that fills them.
+pub fn place_curve_order<'info>(
+    ctx: Context<'_, '_, '_, 'info, PlaceCurveOrder<'info>>,
+    order_id: u64,
+    direction: SwapDirection,
+    amount_in: u64,
+    limit_amount_out: u64,
+) -> Result<()> {
+    let config = &ctx.accounts.config;
+    let owner = &ctx.accounts.owner;
+    let token = &ctx.accounts.token;
+    let token_launch = &ctx.accounts.token_launch;
+    let curve_order = &mut ctx.accounts.curve_order;
+
+    match &token_launch.phase {
+        LaunchPhase::Presale | LaunchPhase::ProcessingPresale | LaunchPhase::OpenSale => {}
+        phase => {
+            msg!(&format!(
+                "curve orders cannot be placed on {phase:?} launches"
+            ));
+            return Err(IncorrectLaunchPhase.into());
+        }
+    }
+    if !token_launch.is_native_quote() {
+        msg!("curve orders are only available to lamport launches");
+        return Err(ValueInvalid.into());
+    }
+    if amount_in == 0 {
+        return Err(NothingToDo.into());
+    }
+    if limit_amount_out == 0 {
+        msg!("limit_amount_out must be positive");
+        return Err(ValueTooSmall.into());
+    }
+
+    curve_order.token = token.key();
+    curve_order.owner = owner.key();
+    curve_order.order_id = order_id;
+    curve_order.direction = direction;
+    curve_order.limit_amount_out = limit_amount_out;
+    curve_order.tip_lamports = config.curve_order_tip_lamports;
+    curve_order.created_at = Clock::get()?.unix_timestamp;
+    curve_order.bump = ctx.bumps.curve_order;
+
+    let escrow_lamports = match direction {
+        SwapDirection::Buy => {
+            curve_order.amount_in = amount_in;
+            curve_order.fee_bps = config.platform_buy_fee_bps;
+
+            amount_in + bps_of(amount_in, curve_order.fee_bps)
+        }
+        SwapDirection::Sell => {
+            let Some(order_token_account) = &ctx.accounts.order_token_account else {
+                msg!("sell orders need the order token account");
+                return Err(MissingAccount.into());
+            };
+
+            // the order sells what arrives in escrow
+            curve_order.amount_in = amount_in - transfer_fee(&token.to_account_info(), amount_in)?;
+            curve_order.fee_bps = config.platform_sell_fee_bps;
+
+            anchor_spl::token_interface::transfer_checked(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    anchor_spl::token_interface::TransferChecked {
+                        from: ctx.accounts.owner_token_account.to_account_info(),
+                        mint: token.to_account_info(),
+                        to: order_token_account.to_account_info(),
+                        authority: owner.to_account_info(),
+                    },
+                ),
+                amount_in,
+                token.decimals,
+            )?;
+
+            0
+        }
+    };
+
+    system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: owner.to_account_info(),
+                to: curve_order.to_account_info(),
+            },
+        ),
+        escrow_lamports + curve_order.tip_lamports,
+    )?;
+
+    Ok(())
+}
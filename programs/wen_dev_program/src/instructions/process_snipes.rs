@@ -0,0 +1,497 @@
+use crate::{
+    constants::{
+        BPS_DENOMINATOR, CONFIG, LAUNCH_STATS, SNIPE_QUEUE, SOL_VAULT, TOKEN_LAUNCH, TRADER_STATE,
+        VESTING,
+    },
+    errors::*,
+    events::SnipeSettled,
+    state::{
+        Config, LaunchPhase, LaunchStats, Referrer, SnipeQueue, SwapDirection, TokenLaunch,
+        TraderState, VestingSchedule,
+    },
+    utils::{bps_of, create_pda_account, transfer_fee, transfer_quote},
+};
+use anchor_lang::{prelude::*, system_program, Discriminator};
+use anchor_spl::{
+    associated_token::{get_associated_token_address_with_program_id, AssociatedToken},
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+#[derive(Accounts)]
+pub struct ProcessSnipes<'info> {
+    /// pays for any sniper token accounts that need to be created
+    #[account(mut)]
+    payer: Signer<'info>,
+
+    #[account(seeds = [CONFIG.as_bytes()], bump)]
+    config: Box<Account<'info, Config>>,
+
+    #[account(mint::token_program = token_program)]
+    token: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(seeds = [TOKEN_LAUNCH.as_bytes(), &token.key().to_bytes()], bump)]
+    token_launch: Box<Account<'info, TokenLaunch>>,
+
+    #[account(
+        mut,
+        seeds = [SNIPE_QUEUE.as_bytes(), &token.key().to_bytes()],
+        bump,
+    )]
+    snipe_queue: Box<Account<'info, SnipeQueue>>,
+
+    #[account(
+        mut,
+        seeds = [LAUNCH_STATS.as_bytes(), &token.key().to_bytes()],
+        bump,
+    )]
+    launch_stats: Box<Account<'info, LaunchStats>>,
+
+    #[account(
+        mut,
+        associated_token::mint = token,
+        associated_token::authority = token_launch,
+        associated_token::token_program = token_program,
+    )]
+    launch_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: lamport-only PDA holding the bonding curve reserves
+    #[account(
+        mut,
+        seeds = [SOL_VAULT.as_bytes(), &token.key().to_bytes()],
+        bump,
+    )]
+    sol_vault: UncheckedAccount<'info>,
+
+    /// CHECK: receives the creator share of winning bids
+    #[account(mut, address = token_launch.creator)]
+    creator: UncheckedAccount<'info>,
+
+    /// CHECK: receives the platform share of winning bids
+    #[account(mut, address = config.fee_wallet)]
+    fee_wallet: UncheckedAccount<'info>,
+
+    /// only for SPL-quoted launches
+    #[account(
+        address = token_launch.quote_mint,
+        mint::token_program = quote_token_program,
+    )]
+    quote_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    /// holds the filled and unfilled `buy_lamports` of SPL-quoted launches
+    #[account(
+        mut,
+        associated_token::mint = quote_mint,
+        associated_token::authority = token_launch,
+        associated_token::token_program = quote_token_program,
+    )]
+    quote_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    quote_token_program: Option<Interface<'info, TokenInterface>>,
+
+    #[account(address = system_program::ID)]
+    system_program: Program<'info, System>,
+
+    token_program: Interface<'info, TokenInterface>,
+
+    #[account(address = anchor_spl::associated_token::ID)]
+    associated_token_program: Program<'info, AssociatedToken>,
+}
+
+/// Settles a batch of snipers.
+///
+/// `remaining_accounts` holds one `(wallet, wallet token account, referrer, trader state)` group
+/// per sniper, all writable, with the program id in place of the referrer for snipes without one.
+/// Launches with vesting append `(vesting schedule, vesting token account)` to each group.
+/// SPL-quoted launches then append the wallet's associated token account for the quote mint,
+/// unfilled `buy_lamports` are refunded there.
+/// Filled snipers receive their tokens, their filled `buy_lamports` go to the sol vault and their
+/// `bid_amount` is split between the creator and the fee wallet, less the referral fee credited
+/// to their referrer. Unfilled snipers get back both their `bid_amount` and `buy_lamports`.
+/// Filled snipes count as curve buys in `LaunchStats`, and their wallets' `TraderState` receipts
+/// are created.
+pub fn process_snipes<'info>(ctx: Context<'_, '_, '_, 'info, ProcessSnipes<'info>>) -> Result<()> {
+    let config = &ctx.accounts.config;
+    let token = &ctx.accounts.token;
+    let token_launch = &ctx.accounts.token_launch;
+
+    token_launch
+        .phase
+        .assert_eq(&LaunchPhase::ProcessingPresale)?;
+
+    let remaining_accounts = ctx.remaining_accounts;
+    if remaining_accounts.is_empty() {
+        return Err(NothingToDo.into());
+    }
+    let vesting_len = if token_launch.vesting.is_some() { 2 } else { 0 };
+    let quote_len = if token_launch.is_native_quote() { 0 } else { 1 };
+    let group_len = 4 + vesting_len + quote_len;
+    let groups = remaining_accounts.chunks_exact(group_len);
+    if !groups.remainder().is_empty() {
+        msg!(&format!(
+            "expected {group_len} accounts per sniper in remaining accounts"
+        ));
+        return Err(ValueInvalid.into());
+    }
+
+    let token_launch_seeds: &[&[&[u8]]] = &[&[
+        TOKEN_LAUNCH.as_bytes(),
+        &token.key().to_bytes(),
+        &[ctx.bumps.token_launch],
+    ]];
+    let snipe_queue_info = ctx.accounts.snipe_queue.to_account_info();
+    let now = Clock::get()?.unix_timestamp;
+
+    for accounts in groups {
+        let (wallet, wallet_token_account) = (&accounts[0], &accounts[1]);
+
+        let index = ctx
+            .accounts
+            .snipe_queue
+            .snipes
+            .iter()
+            .position(|snipe_config| snipe_config.wallet == wallet.key())
+            .ok_or(SnipeNotFound)?;
+        let snipe_config = ctx.accounts.snipe_queue.snipes[index];
+        if snipe_config.processed {
+            msg!("snipe for {} already processed, skipping", wallet.key());
+            continue;
+        }
+
+        // work out where the escrowed lamports go
+        let filled_lamports = snipe_config.buy_lamports - snipe_config.refund_lamports;
+        let (
+            refunded_buy_lamports,
+            refunded_bid_lamports,
+            creator_bid_lamports,
+            platform_bid_lamports,
+        ) = if snipe_config.token_amount == 0 {
+            (snipe_config.buy_lamports, snipe_config.bid_amount, 0, 0)
+        } else {
+            let creator_bid_lamports = ((snipe_config.bid_amount as u128)
+                * (config.bid_creator_share_bps as u128)
+                / (BPS_DENOMINATOR as u128)) as u64;
+            (
+                snipe_config.refund_lamports,
+                0,
+                creator_bid_lamports,
+                snipe_config.bid_amount - creator_bid_lamports,
+            )
+        };
+        let refunded_lamports = refunded_buy_lamports + refunded_bid_lamports;
+
+        // the referrer is credited the volume of filled snipes and its cut of the platform share
+        let referral_fee_lamports = match snipe_config.referrer {
+            Some(referrer_key) if snipe_config.token_amount > 0 => {
+                let referrer_info = &accounts[2];
+                if referrer_info.key() != referrer_key {
+                    msg!(&format!("expected referrer {referrer_key}"));
+                    return Err(ValueInvalid.into());
+                }
+
+                let referral_fee_lamports = bps_of(platform_bid_lamports, config.referral_fee_bps);
+                if *referrer_info.owner != crate::ID {
+                    return Err(ErrorCode::AccountOwnedByWrongProgram.into());
+                }
+                let mut referrer_data = referrer_info.try_borrow_mut_data()?;
+                let mut referrer = Referrer::try_deserialize(&mut &referrer_data[..])?;
+                referrer.record(filled_lamports, referral_fee_lamports)?;
+                referrer.try_serialize(&mut &mut referrer_data[..])?;
+                referral_fee_lamports
+            }
+            _ => 0,
+        };
+        let platform_bid_lamports = platform_bid_lamports - referral_fee_lamports;
+
+        // split the allocation between what is sent now and what vests
+        let (tge_amount, vested_amount) = match &token_launch.vesting {
+            Some(vesting) => {
+                let tge_amount = vesting.tge_amount(snipe_config.token_amount);
+                (tge_amount, snipe_config.token_amount - tge_amount)
+            }
+            None => (snipe_config.token_amount, 0),
+        };
+
+        // deliver tokens
+        if tge_amount > 0 {
+            ctx.accounts.transfer_tokens(
+                wallet,
+                wallet_token_account,
+                tge_amount,
+                token_launch_seeds,
+            )?;
+        }
+
+        if vested_amount > 0 {
+            let vesting = token_launch.vesting.unwrap();
+            let (vesting_schedule, vesting_token_account) = (&accounts[4], &accounts[5]);
+            let (vesting_schedule_key, vesting_schedule_bump) = Pubkey::find_program_address(
+                &[
+                    VESTING.as_bytes(),
+                    &token.key().to_bytes(),
+                    &wallet.key().to_bytes(),
+                ],
+                &crate::ID,
+            );
+            if vesting_schedule.key() != vesting_schedule_key {
+                msg!(&format!("expected vesting schedule {vesting_schedule_key}"));
+                return Err(ValueInvalid.into());
+            }
+
+            // the schedule can only release what arrives after any Token-2022 transfer fee
+            let vesting_amount =
+                vested_amount - transfer_fee(&token.to_account_info(), vested_amount)?;
+            let serialized_schedule = [
+                VestingSchedule::DISCRIMINATOR.as_slice(),
+                VestingSchedule {
+                    token: token.key(),
+                    wallet: wallet.key(),
+                    total_amount: vesting_amount,
+                    claimed_amount: 0,
+                    start_timestamp: token_launch.presale_settled_at,
+                    cliff_seconds: vesting.cliff_seconds,
+                    duration_seconds: vesting.duration_seconds,
+                }
+                .try_to_vec()?
+                .as_slice(),
+            ]
+            .concat();
+
+            create_pda_account(
+                ctx.accounts.payer.to_account_info(),
+                vesting_schedule.clone(),
+                ctx.accounts.system_program.to_account_info(),
+                VestingSchedule::ACCOUNT_LEN,
+                &[&[
+                    VESTING.as_bytes(),
+                    &token.key().to_bytes(),
+                    &wallet.key().to_bytes(),
+                    &[vesting_schedule_bump],
+                ]],
+            )?;
+            vesting_schedule.try_borrow_mut_data()?[..serialized_schedule.len()]
+                .copy_from_slice(&serialized_schedule);
+
+            ctx.accounts.transfer_tokens(
+                vesting_schedule,
+                vesting_token_account,
+                vested_amount,
+                token_launch_seeds,
+            )?;
+        }
+
+        // move escrowed lamports out of the snipe queue, SPL-quoted launches escrow
+        // `buy_lamports` in the quote vault where the filled part already belongs
+        let (escrowed_buy_lamports, wallet_lamports) = if token_launch.is_native_quote() {
+            ctx.accounts.sol_vault.add_lamports(filled_lamports)?;
+            (filled_lamports + refunded_buy_lamports, refunded_lamports)
+        } else {
+            ctx.accounts.refund_quote(
+                wallet,
+                &accounts[group_len - 1],
+                refunded_buy_lamports,
+                token_launch_seeds,
+            )?;
+            (0, refunded_bid_lamports)
+        };
+        snipe_queue_info.sub_lamports(
+            escrowed_buy_lamports
+                + refunded_bid_lamports
+                + creator_bid_lamports
+                + platform_bid_lamports
+                + referral_fee_lamports,
+        )?;
+        wallet.add_lamports(wallet_lamports)?;
+        ctx.accounts.creator.add_lamports(creator_bid_lamports)?;
+        ctx.accounts
+            .fee_wallet
+            .add_lamports(platform_bid_lamports)?;
+        if referral_fee_lamports > 0 {
+            accounts[2].add_lamports(referral_fee_lamports)?;
+        }
+
+        ctx.accounts.snipe_queue.snipes[index].processed = true;
+
+        if snipe_config.token_amount > 0 {
+            if ctx.accounts.open_trader_state(wallet, &accounts[3])? {
+                ctx.accounts.launch_stats.unique_traders += 1;
+            }
+            ctx.accounts.launch_stats.record_trade(
+                token_launch,
+                SwapDirection::Buy,
+                filled_lamports,
+                now,
+            )?;
+        }
+
+        emit!(SnipeSettled {
+            token: token.key(),
+            wallet: wallet.key(),
+            token_amount: snipe_config.token_amount,
+            vested_amount,
+            filled_lamports,
+            refunded_lamports,
+            creator_bid_lamports,
+            platform_bid_lamports,
+            referral_fee_lamports,
+        });
+    }
+
+    Ok(())
+}
+
+impl<'info> ProcessSnipes<'info> {
+    /// creates the `TraderState` receipt of a filled sniper, returns false if it already exists
+    fn open_trader_state(
+        &self,
+        wallet: &AccountInfo<'info>,
+        trader_state: &AccountInfo<'info>,
+    ) -> Result<bool> {
+        let (trader_state_key, trader_state_bump) = Pubkey::find_program_address(
+            &[
+                TRADER_STATE.as_bytes(),
+                &self.token.key().to_bytes(),
+                &wallet.key().to_bytes(),
+            ],
+            &crate::ID,
+        );
+        if trader_state.key() != trader_state_key {
+            msg!(&format!("expected trader state {trader_state_key}"));
+            return Err(ValueInvalid.into());
+        }
+        if !trader_state.data_is_empty() {
+            return Ok(false);
+        }
+
+        let serialized_state = [
+            TraderState::DISCRIMINATOR.as_slice(),
+            TraderState {
+                token: self.token.key(),
+                wallet: wallet.key(),
+                last_buy_slot: None,
+            }
+            .try_to_vec()?
+            .as_slice(),
+        ]
+        .concat();
+
+        create_pda_account(
+            self.payer.to_account_info(),
+            trader_state.clone(),
+            self.system_program.to_account_info(),
+            TraderState::ACCOUNT_LEN,
+            &[&[
+                TRADER_STATE.as_bytes(),
+                &self.token.key().to_bytes(),
+                &wallet.key().to_bytes(),
+                &[trader_state_bump],
+            ]],
+        )?;
+        trader_state.try_borrow_mut_data()?[..serialized_state.len()]
+            .copy_from_slice(&serialized_state);
+
+        Ok(true)
+    }
+
+    /// returns unfilled `buy_lamports` of an SPL-quoted launch to the associated token account of
+    /// `wallet` for the quote mint
+    fn refund_quote(
+        &self,
+        wallet: &AccountInfo<'info>,
+        wallet_quote_account: &AccountInfo<'info>,
+        amount: u64,
+        token_launch_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        let (Some(quote_mint), Some(quote_vault), Some(quote_token_program)) = (
+            &self.quote_mint,
+            &self.quote_vault,
+            &self.quote_token_program,
+        ) else {
+            msg!("SPL-quoted launches need the quote vault and quote token program");
+            return Err(MissingAccount.into());
+        };
+
+        let expected_quote_account = get_associated_token_address_with_program_id(
+            &wallet.key(),
+            &quote_mint.key(),
+            &quote_token_program.key(),
+        );
+        if wallet_quote_account.key() != expected_quote_account {
+            msg!(
+                "expected associated quote token account of {}",
+                wallet.key()
+            );
+            return Err(IncorrectTokenAccount.into());
+        }
+        if amount == 0 {
+            return Ok(());
+        }
+
+        anchor_spl::associated_token::create_idempotent(CpiContext::new(
+            self.associated_token_program.to_account_info(),
+            anchor_spl::associated_token::Create {
+                payer: self.payer.to_account_info(),
+                associated_token: wallet_quote_account.clone(),
+                authority: wallet.clone(),
+                mint: quote_mint.to_account_info(),
+                system_program: self.system_program.to_account_info(),
+                token_program: quote_token_program.to_account_info(),
+            },
+        ))?;
+
+        transfer_quote(
+            quote_token_program,
+            quote_mint,
+            quote_vault.to_account_info(),
+            wallet_quote_account.clone(),
+            self.token_launch.to_account_info(),
+            token_launch_seeds,
+            amount,
+        )
+    }
+
+    /// sends `amount` launch tokens to the associated token account of `owner`, creating it if needed
+    fn transfer_tokens(
+        &self,
+        owner: &AccountInfo<'info>,
+        owner_token_account: &AccountInfo<'info>,
+        amount: u64,
+        token_launch_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        let expected_token_account = get_associated_token_address_with_program_id(
+            &owner.key(),
+            &self.token.key(),
+            &self.token_program.key(),
+        );
+        if owner_token_account.key() != expected_token_account {
+            msg!("expected associated token account of {}", owner.key());
+            return Err(IncorrectTokenAccount.into());
+        }
+
+        anchor_spl::associated_token::create_idempotent(CpiContext::new(
+            self.associated_token_program.to_account_info(),
+            anchor_spl::associated_token::Create {
+                payer: self.payer.to_account_info(),
+                associated_token: owner_token_account.clone(),
+                authority: owner.clone(),
+                mint: self.token.to_account_info(),
+                system_program: self.system_program.to_account_info(),
+                token_program: self.token_program.to_account_info(),
+            },
+        ))?;
+
+        anchor_spl::token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                anchor_spl::token_interface::TransferChecked {
+                    from: self.launch_token_account.to_account_info(),
+                    mint: self.token.to_account_info(),
+                    to: owner_token_account.clone(),
+                    authority: self.token_launch.to_account_info(),
+                },
+                token_launch_seeds,
+            ),
+            amount,
+            self.token.decimals,
+        )
+    }
+}
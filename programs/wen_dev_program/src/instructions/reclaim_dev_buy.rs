@@ -0,0 +1,106 @@
+use crate::{
+    constants::{SOL_VAULT, TOKEN_LAUNCH},
+    errors::*,
+    instructions::refund_dev_buy,
+    state::{LaunchPhase, TokenLaunch},
+};
+use anchor_lang::{prelude::*, system_program};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+#[derive(Accounts)]
+pub struct ReclaimDevBuy<'info> {
+    #[account(mut)]
+    creator: Signer<'info>,
+
+    #[account(mut, mint::token_program = token_program)]
+    token: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [TOKEN_LAUNCH.as_bytes(), &token.key().to_bytes()],
+        bump,
+        has_one = creator @ IncorrectAuthority,
+    )]
+    token_launch: Box<Account<'info, TokenLaunch>>,
+
+    /// holds the creator's dev-buy tokens, burned for the refund
+    #[account(
+        mut,
+        token::mint = token,
+        token::authority = creator,
+        token::token_program = token_program,
+    )]
+    creator_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// CHECK: lamport-only PDA holding the dev buy of lamport launches
+    #[account(
+        mut,
+        seeds = [SOL_VAULT.as_bytes(), &token.key().to_bytes()],
+        bump,
+    )]
+    sol_vault: UncheckedAccount<'info>,
+
+    /// only for SPL-quoted launches
+    #[account(
+        address = token_launch.quote_mint,
+        mint::token_program = quote_token_program,
+    )]
+    quote_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    #[account(
+        mut,
+        associated_token::mint = quote_mint,
+        associated_token::authority = token_launch,
+        associated_token::token_program = quote_token_program,
+    )]
+    quote_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// receives the refund of SPL-quoted launches
+    #[account(
+        mut,
+        token::mint = quote_mint,
+        token::authority = creator,
+        token::token_program = quote_token_program,
+    )]
+    creator_quote_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    quote_token_program: Option<Interface<'info, TokenInterface>>,
+
+    #[account(address = system_program::ID)]
+    system_program: Program<'info, System>,
+
+    token_program: Interface<'info, TokenInterface>,
+}
+
+/// Refunds the dev buy of a cancelled launch against the dev-buy tokens the creator burns, for
+/// launches cancelled by `expire_launch` or dev-buy tokens the creator held back at cancellation.
+pub fn reclaim_dev_buy<'info>(ctx: Context<'_, '_, '_, 'info, ReclaimDevBuy<'info>>) -> Result<()> {
+    let accounts = ctx.accounts;
+
+    accounts
+        .token_launch
+        .phase
+        .assert_eq(&LaunchPhase::Cancelled)?;
+
+    let burned_amount = refund_dev_buy(
+        &accounts.system_program,
+        &accounts.token_program,
+        &accounts.token,
+        &accounts.sol_vault,
+        &accounts.creator,
+        &accounts.creator_token_account,
+        &mut accounts.token_launch,
+        ctx.bumps.sol_vault,
+        ctx.bumps.token_launch,
+        &accounts.quote_mint,
+        &accounts.quote_vault,
+        &accounts.creator_quote_account,
+        &accounts.quote_token_program,
+    )?;
+    if burned_amount == 0 {
+        msg!("no dev-buy tokens to burn for a refund");
+        return Err(NothingToDo.into());
+    }
+
+    Ok(())
+}
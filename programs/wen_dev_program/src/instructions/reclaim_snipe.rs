@@ -0,0 +1,285 @@
+use crate::{
+    constants::{SNIPE_QUEUE, SOL_VAULT, TOKEN_LAUNCH, VESTING},
+    errors::*,
+    state::{LaunchPhase, SnipeQueue, TokenLaunch, VestingSchedule},
+    utils::{burn_tokens, transfer_quote},
+};
+use anchor_lang::{prelude::*, system_program};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+#[derive(Accounts)]
+#[instruction(token: Pubkey)]
+pub struct ReclaimSnipe<'info> {
+    #[account(mut)]
+    wallet: Signer<'info>,
+
+    #[account(seeds = [TOKEN_LAUNCH.as_bytes(), &token.to_bytes()], bump)]
+    token_launch: Box<Account<'info, TokenLaunch>>,
+
+    #[account(
+        mut,
+        seeds = [SNIPE_QUEUE.as_bytes(), &token.to_bytes()],
+        bump,
+    )]
+    snipe_queue: Box<Account<'info, SnipeQueue>>,
+
+    /// only for SPL-quoted launches
+    #[account(
+        address = token_launch.quote_mint,
+        mint::token_program = quote_token_program,
+    )]
+    quote_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    #[account(
+        mut,
+        associated_token::mint = quote_mint,
+        associated_token::authority = token_launch,
+        associated_token::token_program = quote_token_program,
+    )]
+    quote_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    #[account(
+        mut,
+        token::mint = quote_mint,
+        token::authority = wallet,
+        token::token_program = quote_token_program,
+    )]
+    wallet_quote_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    quote_token_program: Option<Interface<'info, TokenInterface>>,
+
+    /// the launch mint, only for snipes settled before their launch expired
+    #[account(
+        mut,
+        address = token,
+        mint::token_program = token_program,
+    )]
+    token_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    #[account(
+        mut,
+        token::mint = token_mint,
+        token::authority = wallet,
+        token::token_program = token_program,
+    )]
+    wallet_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// only for launches with vesting
+    #[account(
+        seeds = [VESTING.as_bytes(), &token.to_bytes(), &wallet.key().to_bytes()],
+        bump,
+    )]
+    vesting_schedule: Option<Box<Account<'info, VestingSchedule>>>,
+
+    #[account(
+        mut,
+        associated_token::mint = token_mint,
+        associated_token::authority = vesting_schedule,
+        associated_token::token_program = token_program,
+    )]
+    vesting_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// CHECK: lamport-only PDA holding the filled `buy_lamports` of lamport launches
+    #[account(
+        mut,
+        seeds = [SOL_VAULT.as_bytes(), &token.to_bytes()],
+        bump,
+    )]
+    sol_vault: Option<UncheckedAccount<'info>>,
+
+    token_program: Option<Interface<'info, TokenInterface>>,
+
+    #[account(address = system_program::ID)]
+    system_program: Option<Program<'info, System>>,
+}
+
+/// Returns the escrowed `bid_amount` and `buy_lamports` of a cancelled launch to the sniper.
+///
+/// Snipes settled by `process_snipes` before the launch expired out of `ProcessingPresale` get
+/// back their filled `buy_lamports` instead, in proportion to the settled tokens they burn.
+pub fn reclaim_snipe<'info>(
+    ctx: Context<'_, '_, '_, 'info, ReclaimSnipe<'info>>,
+    token: Pubkey,
+) -> Result<()> {
+    let wallet = &ctx.accounts.wallet;
+    let token_launch = &ctx.accounts.token_launch;
+
+    token_launch.phase.assert_eq(&LaunchPhase::Cancelled)?;
+
+    let index = ctx
+        .accounts
+        .snipe_queue
+        .snipes
+        .iter()
+        .position(|snipe_config| snipe_config.wallet == wallet.key())
+        .ok_or(SnipeNotFound)?;
+    if ctx.accounts.snipe_queue.snipes[index].processed {
+        if ctx.accounts.snipe_queue.snipes[index].token_amount > 0 {
+            return ctx.accounts.reclaim_settled_snipe(index, token, &ctx.bumps);
+        }
+        msg!("snipe for {} already reclaimed", wallet.key());
+        return Err(NothingToDo.into());
+    }
+    let snipe_queue = &mut ctx.accounts.snipe_queue;
+    let snipe_config = &mut snipe_queue.snipes[index];
+
+    // SPL-quoted launches escrow `buy_lamports` in the quote vault
+    let (escrowed_lamports, escrowed_quote) = if token_launch.is_native_quote() {
+        (snipe_config.bid_amount + snipe_config.buy_lamports, 0)
+    } else {
+        (snipe_config.bid_amount, snipe_config.buy_lamports)
+    };
+    snipe_config.processed = true;
+
+    snipe_queue.sub_lamports(escrowed_lamports)?;
+    wallet.add_lamports(escrowed_lamports)?;
+
+    if escrowed_quote > 0 {
+        let (
+            Some(quote_mint),
+            Some(quote_vault),
+            Some(wallet_quote_account),
+            Some(quote_token_program),
+        ) = (
+            &ctx.accounts.quote_mint,
+            &ctx.accounts.quote_vault,
+            &ctx.accounts.wallet_quote_account,
+            &ctx.accounts.quote_token_program,
+        )
+        else {
+            msg!("SPL-quoted launches need the quote vault and wallet quote account");
+            return Err(MissingAccount.into());
+        };
+
+        transfer_quote(
+            quote_token_program,
+            quote_mint,
+            quote_vault.to_account_info(),
+            wallet_quote_account.to_account_info(),
+            token_launch.to_account_info(),
+            &[&[
+                TOKEN_LAUNCH.as_bytes(),
+                &token.to_bytes(),
+                &[ctx.bumps.token_launch],
+            ]],
+            escrowed_quote,
+        )?;
+    }
+
+    Ok(())
+}
+
+impl<'info> ReclaimSnipe<'info> {
+    /// burns the settled tokens of the snipe at `index` and refunds the matching share of its
+    /// filled `buy_lamports`
+    fn reclaim_settled_snipe(
+        &mut self,
+        index: usize,
+        token: Pubkey,
+        bumps: &ReclaimSnipeBumps,
+    ) -> Result<()> {
+        let (Some(token_mint), Some(wallet_token_account), Some(token_program)) = (
+            &self.token_mint,
+            &self.wallet_token_account,
+            &self.token_program,
+        ) else {
+            msg!("settled snipes need the launch mint and wallet token account");
+            return Err(MissingAccount.into());
+        };
+        let snipe_config = self.snipe_queue.snipes[index];
+
+        // tokens still vesting are burned by their schedule, the wallet burns the rest
+        let vested_amount = match (&self.vesting_schedule, &self.vesting_token_account) {
+            (Some(vesting_schedule), Some(vesting_token_account)) => {
+                let vested_amount = vesting_token_account.amount.min(snipe_config.token_amount);
+                burn_tokens(
+                    token_program,
+                    token_mint,
+                    vesting_token_account.to_account_info(),
+                    vesting_schedule.to_account_info(),
+                    &[&[
+                        VESTING.as_bytes(),
+                        &token.to_bytes(),
+                        &self.wallet.key().to_bytes(),
+                        &[bumps.vesting_schedule],
+                    ]],
+                    vested_amount,
+                )?;
+                vested_amount
+            }
+            _ => 0,
+        };
+        let wallet_amount = wallet_token_account
+            .amount
+            .min(snipe_config.token_amount - vested_amount);
+        burn_tokens(
+            token_program,
+            token_mint,
+            wallet_token_account.to_account_info(),
+            self.wallet.to_account_info(),
+            &[],
+            wallet_amount,
+        )?;
+
+        let burned_amount = vested_amount + wallet_amount;
+        if burned_amount == 0 {
+            msg!("{} holds none of its settled tokens", self.wallet.key());
+            return Err(NothingToDo.into());
+        }
+        let filled_lamports = snipe_config.buy_lamports - snipe_config.refund_lamports;
+        let refunded_lamports = ((filled_lamports as u128) * (burned_amount as u128)
+            / (snipe_config.token_amount as u128)) as u64;
+        self.snipe_queue.snipes[index].token_amount -= burned_amount;
+        self.snipe_queue.snipes[index].buy_lamports -= refunded_lamports;
+
+        if self.token_launch.is_native_quote() {
+            let (Some(sol_vault), Some(system_program)) = (&self.sol_vault, &self.system_program)
+            else {
+                msg!("lamport launches need the sol vault");
+                return Err(MissingAccount.into());
+            };
+
+            return system_program::transfer(
+                CpiContext::new_with_signer(
+                    system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: sol_vault.to_account_info(),
+                        to: self.wallet.to_account_info(),
+                    },
+                    &[&[SOL_VAULT.as_bytes(), &token.to_bytes(), &[bumps.sol_vault]]],
+                ),
+                refunded_lamports,
+            );
+        }
+
+        let (
+            Some(quote_mint),
+            Some(quote_vault),
+            Some(wallet_quote_account),
+            Some(quote_token_program),
+        ) = (
+            &self.quote_mint,
+            &self.quote_vault,
+            &self.wallet_quote_account,
+            &self.quote_token_program,
+        )
+        else {
+            msg!("SPL-quoted launches need the quote vault and wallet quote account");
+            return Err(MissingAccount.into());
+        };
+
+        transfer_quote(
+            quote_token_program,
+            quote_mint,
+            quote_vault.to_account_info(),
+            wallet_quote_account.to_account_info(),
+            self.token_launch.to_account_info(),
+            &[&[
+                TOKEN_LAUNCH.as_bytes(),
+                &token.to_bytes(),
+                &[bumps.token_launch],
+            ]],
+            refunded_lamports,
+        )
+    }
+}
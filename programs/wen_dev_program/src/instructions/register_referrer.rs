@@ -0,0 +1,33 @@
+use crate::{constants::REFERRER, state::Referrer};
+use anchor_lang::{prelude::*, system_program};
+
+#[derive(Accounts)]
+pub struct RegisterReferrer<'info> {
+    #[account(mut)]
+    wallet: Signer<'info>,
+
+    #[account(
+        init,
+        payer = wallet,
+        space = Referrer::ACCOUNT_LEN,
+        seeds = [REFERRER.as_bytes(), &wallet.key().to_bytes()],
+        bump,
+    )]
+    referrer: Box<Account<'info, Referrer>>,
+
+    #[account(address = system_program::ID)]
+    system_program: Program<'info, System>,
+}
+
+pub fn register_referrer<'info>(
+    ctx: Context<'_, '_, '_, 'info, RegisterReferrer<'info>>,
+) -> Result<()> {
+    let referrer = &mut ctx.accounts.referrer;
+
+    referrer.wallet = ctx.accounts.wallet.key();
+    referrer.claimable_lamports = 0;
+    referrer.claimed_lamports = 0;
+    referrer.referred_volume = 0;
+
+    Ok(())
+}
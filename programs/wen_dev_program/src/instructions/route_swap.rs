@@ -0,0 +1,103 @@
+use crate::{
+    constants::TOKEN_LAUNCH,
+    errors::*,
+    instructions::{
+        route_curve_swap, swap_with_platform_fee, ProxySwapBaseIn, ProxySwapBaseInBumps,
+    },
+    state::{LaunchPhase, SwapAmount, SwapDeadline, SwapDirection, TokenLaunch},
+    utils::{load_amm_keys, unpack_token_account},
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenInterface};
+use std::collections::BTreeSet;
+
+#[derive(Accounts)]
+pub struct RouteSwap<'info> {
+    #[account(mint::token_program = token_program)]
+    token: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: `close_launch` closes the launch once migrated, read in the handler while it exists
+    #[account(
+        seeds = [TOKEN_LAUNCH.as_bytes(), &token.key().to_bytes()],
+        bump,
+    )]
+    token_launch: UncheckedAccount<'info>,
+
+    token_program: Interface<'info, TokenInterface>,
+}
+
+/// Trades a launch's token wherever it currently trades.
+///
+/// Until the launch is migrated `remaining_accounts` holds the `swap` accounts and the trade goes
+/// against the bonding curve, as `swap` or `swap_exact_out`. Once migrated it holds the
+/// `proxy_swap_base_in` accounts and the trade goes through the Raydium pool, as
+/// `amm_anchor::swap_base_in` or `amm_anchor::swap_base_out`, paying the same platform fee as the
+/// proxy. `direction` is relative to the token in both cases.
+pub fn route_swap<'info>(
+    ctx: Context<'_, '_, 'info, 'info, RouteSwap<'info>>,
+    direction: SwapDirection,
+    swap_amount: SwapAmount,
+    deadline: Option<SwapDeadline>,
+) -> Result<()> {
+    let token = &ctx.accounts.token;
+    let token_launch = &ctx.accounts.token_launch;
+
+    // a closed launch account was migrated first
+    let migrated = token_launch.data_is_empty()
+        || TokenLaunch::try_deserialize(&mut &token_launch.try_borrow_data()?[..])?.phase
+            == LaunchPhase::Migrated;
+    if !migrated {
+        return route_curve_swap(
+            ctx.program_id,
+            token_launch.key(),
+            ctx.remaining_accounts,
+            direction,
+            swap_amount,
+            deadline,
+        );
+    }
+
+    if let Some(deadline) = &deadline {
+        deadline.check()?;
+    }
+
+    let mut remaining_accounts = ctx.remaining_accounts;
+    let mut accounts = ProxySwapBaseIn::try_accounts(
+        ctx.program_id,
+        &mut remaining_accounts,
+        &[],
+        &mut ProxySwapBaseInBumps::default(),
+        &mut BTreeSet::new(),
+    )?;
+
+    // the pool must trade the token, on either side
+    let amm_keys = load_amm_keys(&accounts.amm)?;
+    if token.key() != amm_keys.coin_vault_mint && token.key() != amm_keys.pc_vault_mint {
+        msg!("pool {} does not trade {}", accounts.amm.key(), token.key());
+        return Err(IncorrectAmmAccount.into());
+    }
+
+    // sells trade the token away, buys receive it
+    let user_token_account = match direction {
+        SwapDirection::Buy => &accounts.user_token_destination,
+        SwapDirection::Sell => &accounts.user_token_source,
+    };
+    let user_token_mint = unpack_token_account(user_token_account)?.mint;
+    if user_token_mint != token.key() {
+        msg!(
+            "{direction:?} expects a {} account, got {}",
+            token.key(),
+            user_token_account.key()
+        );
+        return Err(IncorrectTokenAccount.into());
+    }
+
+    let cpi_ctx = (&mut accounts).into();
+    swap_with_platform_fee(
+        cpi_ctx,
+        &accounts.config,
+        &accounts.quote_mint,
+        &accounts.fee_wallet_quote_account,
+        swap_amount,
+    )
+}
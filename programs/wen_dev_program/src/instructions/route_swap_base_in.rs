@@ -0,0 +1,261 @@
+use crate::{
+    constants::CONFIG,
+    errors::*,
+    events::ProxySwap,
+    instructions::{transfer_platform_fee, validate_swap_accounts},
+    state::{Config, SwapDirection},
+    utils::{bps_of, unpack_token_account},
+};
+use amm_anchor::SwapBaseIn;
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+/// accounts of one pool in `remaining_accounts`, ordered as in `ProxySwapBaseIn`
+const LEG_ACCOUNTS: usize = 15;
+
+#[derive(Accounts)]
+pub struct ProxyRouteSwap<'info> {
+    /// CHECK: Safe. The AMM program
+    #[account(address = amm_anchor::ID)]
+    pub amm_program: UncheckedAccount<'info>,
+    /// CHECK: Safe. user owner Account
+    #[account(mut)]
+    pub user_source_owner: Signer<'info>,
+    /// CHECK: Safe. The spl token program
+    pub token_program: Program<'info, Token>,
+    #[account(seeds = [CONFIG.as_bytes()], bump)]
+    pub config: Box<Account<'info, Config>>,
+    /// mint the route is charged the platform fee in, if it trades it
+    pub quote_mint: Box<Account<'info, Mint>>,
+    /// receives platform fees
+    #[account(
+        mut,
+        associated_token::mint = quote_mint,
+        associated_token::authority = config.fee_wallet,
+    )]
+    pub fee_wallet_quote_account: Box<Account<'info, TokenAccount>>,
+}
+
+/// Swaps through several AMM pools in a row, e.g. TOKEN -> SOL -> USDC.
+///
+/// `remaining_accounts` holds 15 accounts per pool, `amm` to `user_token_destination` in the order
+/// of `ProxySwapBaseIn`. Each pool's source account must be the previous pool's destination, and
+/// trades everything the previous pool paid into it. Only the final output is checked against
+/// `minimum_amount_out`.
+///
+/// The platform fee is charged once for the whole route, as in `proxy_swap_base_in`: routes
+/// starting from `quote_mint` pay `Config.platform_buy_fee_bps` of `amount_in` on top, other
+/// routes keep `Config.platform_sell_fee_bps` of the first leg output in `quote_mint`, be it the
+/// final output or an intermediate one like the SOL of TOKEN_A -> SOL -> TOKEN_B. Routes that
+/// never trade `quote_mint` pay no fee. `minimum_amount_out` applies after the fee.
+pub fn route_swap_base_in<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ProxyRouteSwap<'info>>,
+    amount_in: u64,
+    minimum_amount_out: u64,
+) -> Result<()> {
+    let legs = ctx.remaining_accounts.chunks_exact(LEG_ACCOUNTS);
+    if ctx.remaining_accounts.is_empty() || !legs.remainder().is_empty() {
+        msg!(&format!(
+            "expected {LEG_ACCOUNTS} accounts per pool in remaining accounts"
+        ));
+        return Err(ValueInvalid.into());
+    }
+
+    let config = &ctx.accounts.config;
+    let quote_mint = &ctx.accounts.quote_mint;
+    // only allowed quote mints are charged in
+    config.quote_limits(&quote_mint.key())?;
+
+    let route_source = &ctx.remaining_accounts[13];
+    let mut destination_mints = vec![];
+    for accounts in legs.clone() {
+        destination_mints.push(unpack_token_account(&accounts[14])?.mint);
+    }
+    let fee_point = fee_point(
+        &quote_mint.key(),
+        &unpack_token_account(route_source)?.mint,
+        &destination_mints,
+    );
+
+    let mut fee: Option<(Pubkey, SwapDirection, u64, u64, &AccountInfo<'info>)> = None;
+    if fee_point == Some(FeePoint::Input) {
+        let fee_amount = bps_of(amount_in, config.platform_buy_fee_bps);
+        fee = Some((
+            ctx.remaining_accounts[0].key(),
+            SwapDirection::Buy,
+            amount_in,
+            fee_amount,
+            route_source,
+        ));
+    }
+
+    let mut leg_amount_in = amount_in;
+    let mut previous_destination: Option<&AccountInfo<'info>> = None;
+    for (index, accounts) in legs.enumerate() {
+        let (user_token_source, user_token_destination) = (&accounts[13], &accounts[14]);
+        if let Some(previous_destination) = previous_destination {
+            if user_token_source.key() != previous_destination.key() {
+                msg!(
+                    "pool {} must trade from {}, the previous pool's destination",
+                    accounts[0].key(),
+                    previous_destination.key()
+                );
+                return Err(IncorrectTokenAccount.into());
+            }
+        }
+
+        let balance_before = token_balance(user_token_destination)?;
+        let swap_accounts = SwapBaseIn {
+            amm: UncheckedAccount::try_from(&accounts[0]),
+            amm_authority: UncheckedAccount::try_from(&accounts[1]),
+            amm_open_orders: UncheckedAccount::try_from(&accounts[2]),
+            amm_coin_vault: UncheckedAccount::try_from(&accounts[3]),
+            amm_pc_vault: UncheckedAccount::try_from(&accounts[4]),
+            market_program: UncheckedAccount::try_from(&accounts[5]),
+            market: UncheckedAccount::try_from(&accounts[6]),
+            market_bids: UncheckedAccount::try_from(&accounts[7]),
+            market_asks: UncheckedAccount::try_from(&accounts[8]),
+            market_event_queue: UncheckedAccount::try_from(&accounts[9]),
+            market_coin_vault: UncheckedAccount::try_from(&accounts[10]),
+            market_pc_vault: UncheckedAccount::try_from(&accounts[11]),
+            market_vault_signer: UncheckedAccount::try_from(&accounts[12]),
+            user_token_source: UncheckedAccount::try_from(user_token_source),
+            user_token_destination: UncheckedAccount::try_from(user_token_destination),
+            user_source_owner: ctx.accounts.user_source_owner.clone(),
+            token_program: ctx.accounts.token_program.clone(),
+        };
+        validate_swap_accounts(&ctx.accounts.amm_program, &swap_accounts)?;
+        amm_anchor::swap_base_in(
+            CpiContext::new(ctx.accounts.amm_program.to_account_info(), swap_accounts),
+            leg_amount_in,
+            0,
+        )?;
+
+        leg_amount_in = token_balance(user_token_destination)? - balance_before;
+        previous_destination = Some(user_token_destination);
+
+        // the sell fee comes out of the first quote tokens the route receives, the next leg
+        // trading what is left
+        if fee_point == Some(FeePoint::LegOutput(index)) {
+            let fee_amount = bps_of(leg_amount_in, config.platform_sell_fee_bps);
+            fee = Some((
+                accounts[0].key(),
+                SwapDirection::Sell,
+                leg_amount_in,
+                fee_amount,
+                user_token_destination,
+            ));
+            leg_amount_in -= fee_amount;
+        }
+    }
+
+    let amount_out = leg_amount_in;
+    if amount_out < minimum_amount_out {
+        msg!(&format!("route returns {amount_out} after the platform fee, expected at least {minimum_amount_out}"));
+        return Err(SlippageExceeded.into());
+    }
+
+    let Some((amm, direction, quote_amount, fee_amount, quote_account)) = fee else {
+        msg!("route never trades {}, no platform fee", quote_mint.key());
+        return Ok(());
+    };
+    transfer_platform_fee(
+        ctx.accounts.token_program.to_account_info(),
+        quote_account.clone(),
+        quote_mint,
+        &ctx.accounts.fee_wallet_quote_account,
+        ctx.accounts.user_source_owner.to_account_info(),
+        fee_amount,
+    )?;
+
+    let (quote_amount_after_fee, token_amount) = match direction {
+        SwapDirection::Buy => (quote_amount + fee_amount, amount_out),
+        SwapDirection::Sell => (quote_amount - fee_amount, amount_in),
+    };
+    emit!(ProxySwap {
+        amm,
+        user: ctx.accounts.user_source_owner.key(),
+        direction,
+        quote_amount_before_fee: quote_amount,
+        quote_amount_after_fee,
+        fee_amount,
+        token_amount,
+    });
+
+    Ok(())
+}
+
+/// Where a route pays the platform fee
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum FeePoint {
+    /// on top of the input of a route starting from the quote mint
+    Input,
+    /// out of the output of the leg at this index, the first one paying into the quote mint
+    LegOutput(usize),
+}
+
+/// finds where a route from `source_mint` through legs paying into `destination_mints` is charged,
+/// `None` if it never touches `quote_mint`
+fn fee_point(
+    quote_mint: &Pubkey,
+    source_mint: &Pubkey,
+    destination_mints: &[Pubkey],
+) -> Option<FeePoint> {
+    if source_mint == quote_mint {
+        return Some(FeePoint::Input);
+    }
+
+    destination_mints
+        .iter()
+        .position(|mint| mint == quote_mint)
+        .map(FeePoint::LegOutput)
+}
+
+fn token_balance(token_account: &AccountInfo) -> Result<u64> {
+    Ok(unpack_token_account(token_account)?.amount)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fee_point_charges_the_first_quote_mint_of_the_route() {
+        let (sol, usdc, token_a) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+
+        // SOL -> TOKEN_A pays on top of its input
+        assert_eq!(fee_point(&sol, &sol, &[token_a]), Some(FeePoint::Input));
+        // TOKEN_A -> SOL pays out of its output
+        assert_eq!(
+            fee_point(&sol, &token_a, &[sol]),
+            Some(FeePoint::LegOutput(0))
+        );
+        // TOKEN_A -> USDC -> SOL pays out of the final output
+        assert_eq!(
+            fee_point(&sol, &token_a, &[usdc, sol]),
+            Some(FeePoint::LegOutput(1))
+        );
+    }
+
+    #[test]
+    fn fee_point_charges_routes_without_quote_mint_ends_on_the_intermediate_leg() {
+        let (sol, usdc, token_a, token_b) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+
+        // TOKEN_A -> SOL -> TOKEN_B pays out of the SOL in between
+        assert_eq!(
+            fee_point(&sol, &token_a, &[sol, token_b]),
+            Some(FeePoint::LegOutput(0))
+        );
+        // TOKEN_A -> USDC -> TOKEN_B never trades SOL and is not charged
+        assert_eq!(fee_point(&sol, &token_a, &[usdc, token_b]), None);
+    }
+}
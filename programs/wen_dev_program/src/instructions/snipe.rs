@@ -1,9 +1,11 @@
 use crate::{
     constants::{SNIPE_QUEUE, TOKEN_LAUNCH},
     errors::*,
-    state::{LaunchPhase, SnipeConfig, SnipeQueue, TokenLaunch},
+    state::{LaunchPhase, Referrer, SnipeConfig, SnipeQueue, TokenLaunch},
+    utils::transfer_quote,
 };
 use anchor_lang::{prelude::*, system_program};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 use std::cmp::Ordering;
 
 #[derive(Accounts)]
@@ -22,6 +24,35 @@ pub struct Snipe<'info> {
     )]
     snipe_queue: Box<Account<'info, SnipeQueue>>,
 
+    /// credited with part of the platform share of the bid once the snipe is filled
+    referrer: Option<Box<Account<'info, Referrer>>>,
+
+    /// only for SPL-quoted launches
+    #[account(
+        address = token_launch.quote_mint,
+        mint::token_program = quote_token_program,
+    )]
+    quote_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    /// escrows `buy_lamports` of SPL-quoted launches
+    #[account(
+        mut,
+        associated_token::mint = quote_mint,
+        associated_token::authority = token_launch,
+        associated_token::token_program = quote_token_program,
+    )]
+    quote_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    #[account(
+        mut,
+        token::mint = quote_mint,
+        token::authority = wallet,
+        token::token_program = quote_token_program,
+    )]
+    wallet_quote_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    quote_token_program: Option<Interface<'info, TokenInterface>>,
+
     #[account(address = system_program::ID)]
     system_program: Program<'info, System>,
 }
@@ -42,6 +73,7 @@ pub fn snipe<'info>(
     let snipe_queue = &mut ctx.accounts.snipe_queue;
 
     token_launch.phase.assert_eq(&LaunchPhase::Presale)?;
+    token_launch.assert_presale_open(Clock::get()?.unix_timestamp)?;
 
     // TODO: charge user for ATA creation + tx fee
 
@@ -56,6 +88,15 @@ pub fn snipe<'info>(
         }
     }
 
+    let referrer = match &ctx.accounts.referrer {
+        Some(referrer) if referrer.wallet == wallet.key() => {
+            msg!("{} cannot refer their own snipe", wallet.key());
+            return Err(SelfReferral.into());
+        }
+        Some(referrer) => Some(referrer.key()),
+        None => None,
+    };
+
     // see if this is creating a new snipe or updating an existing one
     match snipe_queue
         .snipes
@@ -64,17 +105,26 @@ pub fn snipe<'info>(
     {
         // existing snipe, update values
         Some(snipe_config) => {
-            let old_balance = snipe_config.bid_amount + snipe_config.buy_lamports;
+            let old_snipe_config = *snipe_config;
             if let Some(bid_amount) = bid_amount {
                 snipe_config.bid_amount = bid_amount;
             }
             if let Some(buy_lamports) = buy_lamports {
                 snipe_config.buy_lamports = buy_lamports;
             }
+            if referrer.is_some() {
+                snipe_config.referrer = referrer;
+            }
 
-            // balance adjustments
-            let new_balance = snipe_config.bid_amount + snipe_config.buy_lamports;
-            let delta = (old_balance as i64) - (new_balance as i64);
+            // balance adjustments, SPL-quoted launches escrow `buy_lamports` in the quote vault
+            let bid_delta = (old_snipe_config.bid_amount as i64) - (snipe_config.bid_amount as i64);
+            let buy_delta =
+                (old_snipe_config.buy_lamports as i64) - (snipe_config.buy_lamports as i64);
+            let (delta, quote_delta) = if token_launch.is_native_quote() {
+                (bid_delta + buy_delta, 0)
+            } else {
+                (bid_delta, buy_delta)
+            };
 
             match delta.cmp(&0) {
                 // deficit, transfer from user -> snipe queue
@@ -111,6 +161,50 @@ pub fn snipe<'info>(
                 // equal, no change
                 Ordering::Equal => {}
             }
+
+            if quote_delta != 0 {
+                let (
+                    Some(quote_mint),
+                    Some(quote_vault),
+                    Some(wallet_quote_account),
+                    Some(quote_token_program),
+                ) = (
+                    &ctx.accounts.quote_mint,
+                    &ctx.accounts.quote_vault,
+                    &ctx.accounts.wallet_quote_account,
+                    &ctx.accounts.quote_token_program,
+                )
+                else {
+                    msg!("SPL-quoted launches need the quote vault and wallet quote account");
+                    return Err(MissingAccount.into());
+                };
+
+                if quote_delta < 0 {
+                    transfer_quote(
+                        quote_token_program,
+                        quote_mint,
+                        wallet_quote_account.to_account_info(),
+                        quote_vault.to_account_info(),
+                        wallet.to_account_info(),
+                        &[],
+                        -quote_delta as u64,
+                    )?;
+                } else {
+                    transfer_quote(
+                        quote_token_program,
+                        quote_mint,
+                        quote_vault.to_account_info(),
+                        wallet_quote_account.to_account_info(),
+                        token_launch.to_account_info(),
+                        &[&[
+                            TOKEN_LAUNCH.as_bytes(),
+                            &token.to_bytes(),
+                            &[ctx.bumps.token_launch],
+                        ]],
+                        quote_delta as u64,
+                    )?;
+                }
+            }
         }
 
         // new snipe
@@ -121,7 +215,9 @@ pub fn snipe<'info>(
                     bid_amount,
                     buy_lamports,
                     token_amount: 0,
+                    refund_lamports: 0,
                     processed: false,
+                    referrer,
                 },
                 _ => {
                     println!(
@@ -135,11 +231,39 @@ pub fn snipe<'info>(
             // increase account length
             let snipe_queue_account_info = snipe_queue.to_account_info();
             let new_snipe_queue_len = snipe_queue_account_info.data_len() + SnipeConfig::DATA_LEN;
-            let new_snipe_queue_cost = Rent::get()?.minimum_balance(new_snipe_queue_len);
+            // the sniper pays for the space they add, returned by `close_launch`
+            let lamport_delta = SnipeQueue::snipe_rent()?;
+            let escrowed_buy_lamports = if token_launch.is_native_quote() {
+                snipe_config.buy_lamports
+            } else {
+                let (
+                    Some(quote_mint),
+                    Some(quote_vault),
+                    Some(wallet_quote_account),
+                    Some(quote_token_program),
+                ) = (
+                    &ctx.accounts.quote_mint,
+                    &ctx.accounts.quote_vault,
+                    &ctx.accounts.wallet_quote_account,
+                    &ctx.accounts.quote_token_program,
+                )
+                else {
+                    msg!("SPL-quoted launches need the quote vault and wallet quote account");
+                    return Err(MissingAccount.into());
+                };
 
-            let lamport_delta = new_snipe_queue_cost.saturating_sub(snipe_queue.get_lamports());
-            let transfer_amount =
-                snipe_config.bid_amount + snipe_config.buy_lamports + lamport_delta;
+                transfer_quote(
+                    quote_token_program,
+                    quote_mint,
+                    wallet_quote_account.to_account_info(),
+                    quote_vault.to_account_info(),
+                    wallet.to_account_info(),
+                    &[],
+                    snipe_config.buy_lamports,
+                )?;
+                0
+            };
+            let transfer_amount = snipe_config.bid_amount + escrowed_buy_lamports + lamport_delta;
             system_program::transfer(
                 CpiContext::new(
                     ctx.accounts.system_program.to_account_info(),
@@ -0,0 +1,622 @@
+use crate::{
+    constants::{
+        BPS_DENOMINATOR, CONFIG, LAUNCH_STATS, PRICE_OBSERVATIONS, SOL_VAULT, TOKEN_LAUNCH,
+        TRADER_STATE,
+    },
+    errors::*,
+    events::Trade,
+    state::{
+        Config, LaunchPhase, LaunchStats, PriceObservations, Referrer, SwapAmount, SwapDeadline,
+        SwapDirection, TokenLaunch, TraderState,
+    },
+    utils::{bps_of, pre_transfer_fee_amount, transfer_fee, transfer_quote},
+};
+use anchor_lang::{prelude::*, system_program};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+use std::collections::BTreeSet;
+
+#[derive(Accounts)]
+pub struct Swap<'info> {
+    #[account(mut)]
+    user: Signer<'info>,
+
+    #[account(seeds = [CONFIG.as_bytes()], bump)]
+    config: Box<Account<'info, Config>>,
+
+    /// CHECK: receives platform fees
+    #[account(mut, address = config.fee_wallet)]
+    fee_wallet: UncheckedAccount<'info>,
+
+    #[account(mint::token_program = token_program)]
+    token: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [TOKEN_LAUNCH.as_bytes(), &token.key().to_bytes()],
+        bump,
+    )]
+    token_launch: Box<Account<'info, TokenLaunch>>,
+
+    #[account(
+        mut,
+        associated_token::mint = token,
+        associated_token::authority = token_launch,
+        associated_token::token_program = token_program,
+    )]
+    launch_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [PRICE_OBSERVATIONS.as_bytes(), &token.key().to_bytes()],
+        bump,
+    )]
+    price_observations: Box<Account<'info, PriceObservations>>,
+
+    #[account(
+        mut,
+        seeds = [LAUNCH_STATS.as_bytes(), &token.key().to_bytes()],
+        bump,
+    )]
+    launch_stats: Box<Account<'info, LaunchStats>>,
+
+    /// CHECK: lamport-only PDA holding the bonding curve reserves
+    #[account(
+        mut,
+        seeds = [SOL_VAULT.as_bytes(), &token.key().to_bytes()],
+        bump,
+    )]
+    sol_vault: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = token,
+        associated_token::authority = user,
+        associated_token::token_program = token_program,
+    )]
+    user_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = TraderState::ACCOUNT_LEN,
+        seeds = [TRADER_STATE.as_bytes(), &token.key().to_bytes(), &user.key().to_bytes()],
+        bump,
+    )]
+    trader_state: Box<Account<'info, TraderState>>,
+
+    /// CHECK: receives sandwich penalties
+    #[account(mut, address = token_launch.creator)]
+    creator: UncheckedAccount<'info>,
+
+    /// credited with `Config.referral_fee_bps` of the platform fee, rejected on SPL-quoted launches
+    #[account(mut)]
+    referrer: Option<Box<Account<'info, Referrer>>>,
+
+    /// only for SPL-quoted launches
+    #[account(
+        address = token_launch.quote_mint,
+        mint::token_program = quote_token_program,
+    )]
+    quote_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    #[account(
+        mut,
+        associated_token::mint = quote_mint,
+        associated_token::authority = token_launch,
+        associated_token::token_program = quote_token_program,
+    )]
+    quote_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    #[account(
+        mut,
+        token::mint = quote_mint,
+        token::authority = user,
+        token::token_program = quote_token_program,
+    )]
+    user_quote_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// receives platform fees of SPL-quoted launches
+    #[account(
+        mut,
+        associated_token::mint = quote_mint,
+        associated_token::authority = fee_wallet,
+        associated_token::token_program = quote_token_program,
+    )]
+    fee_wallet_quote_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// receives sandwich penalties of SPL-quoted launches
+    #[account(
+        mut,
+        token::mint = quote_mint,
+        token::authority = creator,
+        token::token_program = quote_token_program,
+    )]
+    creator_quote_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    quote_token_program: Option<Interface<'info, TokenInterface>>,
+
+    #[account(address = system_program::ID)]
+    system_program: Program<'info, System>,
+
+    token_program: Interface<'info, TokenInterface>,
+
+    #[account(address = anchor_spl::associated_token::ID)]
+    associated_token_program: Program<'info, AssociatedToken>,
+}
+
+/// Trades an exact amount in against the bonding curve.
+///
+/// Buys spend `amount_in` lamports on the curve with the platform fee charged on top, and are cut
+/// short if they would take the curve past its graduation target. Sells sell `amount_in` tokens,
+/// the platform fee is taken out of the lamports received.
+///
+/// SPL-quoted launches trade and pay fees in `quote_mint` instead of lamports.
+///
+/// For Token-2022 launches with a transfer fee, `minimum_amount_out` of a buy applies to the tokens
+/// left after the fee is withheld, and a sell is priced on the tokens the curve actually receives.
+///
+/// Trades are rejected once `deadline` has passed.
+///
+/// Sells within `Config.sandwich_window_slots` of the same wallet's last buy are rejected or pay a
+/// penalty to the creator, as `Config.sandwich_policy` says.
+pub fn swap<'info>(
+    ctx: Context<'_, '_, '_, 'info, Swap<'info>>,
+    direction: SwapDirection,
+    amount_in: u64,
+    minimum_amount_out: u64,
+    deadline: Option<SwapDeadline>,
+) -> Result<()> {
+    execute_swap(
+        ctx,
+        direction,
+        SwapAmount::ExactIn {
+            amount_in,
+            minimum_amount_out,
+        },
+        deadline,
+    )
+}
+
+/// Trades for an exact amount out against the bonding curve.
+///
+/// Buys receive `amount_out` tokens after transfer fees, spending at most `max_amount_in` lamports
+/// on the curve with the platform fee charged on top. A buy that would take the curve past its
+/// graduation target fails instead of being cut short.
+/// Sells receive `amount_out` lamports after the platform fee, selling at most `max_amount_in`
+/// tokens including transfer fees.
+pub fn swap_exact_out<'info>(
+    ctx: Context<'_, '_, '_, 'info, Swap<'info>>,
+    direction: SwapDirection,
+    max_amount_in: u64,
+    amount_out: u64,
+    deadline: Option<SwapDeadline>,
+) -> Result<()> {
+    execute_swap(
+        ctx,
+        direction,
+        SwapAmount::ExactOut {
+            max_amount_in,
+            amount_out,
+        },
+        deadline,
+    )
+}
+
+/// Trades on the curve for `route_swap`, taking the `Swap` accounts from `accounts`
+pub(crate) fn route_curve_swap<'info>(
+    program_id: &Pubkey,
+    token_launch: Pubkey,
+    accounts: &'info [AccountInfo<'info>],
+    direction: SwapDirection,
+    swap_amount: SwapAmount,
+    deadline: Option<SwapDeadline>,
+) -> Result<()> {
+    let mut remaining_accounts = accounts;
+    let mut bumps = SwapBumps::default();
+    let mut swap = Swap::try_accounts(
+        program_id,
+        &mut remaining_accounts,
+        &[],
+        &mut bumps,
+        &mut BTreeSet::new(),
+    )?;
+    if swap.token_launch.key() != token_launch {
+        msg!(&format!("expected the curve accounts of {token_launch}"));
+        return Err(ValueInvalid.into());
+    }
+
+    execute_swap(
+        Context::new(program_id, &mut swap, remaining_accounts, bumps),
+        direction,
+        swap_amount,
+        deadline,
+    )?;
+
+    // not run by the entrypoint for accounts parsed here
+    swap.exit(program_id)
+}
+
+fn execute_swap<'info>(
+    ctx: Context<'_, '_, '_, 'info, Swap<'info>>,
+    direction: SwapDirection,
+    swap_amount: SwapAmount,
+    deadline: Option<SwapDeadline>,
+) -> Result<()> {
+    let config = &ctx.accounts.config;
+    let token = &ctx.accounts.token;
+    let user = &ctx.accounts.user;
+    let token_launch = &mut ctx.accounts.token_launch;
+
+    token_launch.phase.assert_eq(&LaunchPhase::OpenSale)?;
+    if let Some(deadline) = &deadline {
+        deadline.check()?;
+    }
+
+    let (minimum_amount_out, max_amount_in) = match swap_amount {
+        SwapAmount::ExactIn {
+            amount_in,
+            minimum_amount_out,
+        } => (minimum_amount_out, amount_in),
+        SwapAmount::ExactOut {
+            max_amount_in,
+            amount_out,
+        } => (amount_out, max_amount_in),
+    };
+    if max_amount_in == 0 {
+        return Err(NothingToDo.into());
+    }
+
+    // referral fees come out of the platform fee
+    let referral_fee_bps = match &ctx.accounts.referrer {
+        Some(referrer) if referrer.wallet == user.key() => {
+            msg!("{} cannot refer their own trade", user.key());
+            return Err(SelfReferral.into());
+        }
+        Some(_) if !token_launch.is_native_quote() => {
+            msg!("referral fees are only paid on lamport launches");
+            return Err(ReferralUnsupported.into());
+        }
+        Some(_) => config.referral_fee_bps,
+        None => 0,
+    };
+    let referrer_info = ctx
+        .accounts
+        .referrer
+        .as_ref()
+        .map(|referrer| referrer.to_account_info());
+
+    let clock = Clock::get()?;
+    let slot = clock.slot;
+    let trader_state = &mut ctx.accounts.trader_state;
+    // a fresh receipt is a new trader
+    if trader_state.wallet == Pubkey::default() {
+        ctx.accounts.launch_stats.unique_traders += 1;
+    }
+    trader_state.token = token.key();
+    trader_state.wallet = user.key();
+
+    let (
+        lamport_amount,
+        token_amount,
+        fee_lamports,
+        referral_fee_lamports,
+        penalty_lamports,
+        transfer_fee_amount,
+    ) = match direction {
+        SwapDirection::Buy => {
+            let (lamport_amount, token_amount) = match swap_amount {
+                // never take the curve past graduation
+                SwapAmount::ExactIn { amount_in, .. } => {
+                    let lamport_amount = amount_in.min(token_launch.lamports_to_graduation());
+                    (lamport_amount, token_launch.quote_buy(lamport_amount)?)
+                }
+                // the tokens bought must cover the transfer fee on top of `amount_out`
+                SwapAmount::ExactOut { amount_out, .. } => {
+                    let token_amount =
+                        pre_transfer_fee_amount(&token.to_account_info(), amount_out)?;
+                    let lamport_amount = token_launch.quote_lamports_for_tokens(token_amount)?;
+                    if lamport_amount > token_launch.lamports_to_graduation() {
+                        msg!("buying {token_amount} tokens costs {lamport_amount} lamports, only {} left before graduation", token_launch.lamports_to_graduation());
+                        return Err(GraduationExceeded.into());
+                    }
+                    if lamport_amount > max_amount_in {
+                        msg!(&format!(
+                            "buy costs {lamport_amount} lamports, expected at most {max_amount_in}"
+                        ));
+                        return Err(SlippageExceeded.into());
+                    }
+                    (lamport_amount, token_amount)
+                }
+            };
+            let fee_lamports = bps_of(lamport_amount, config.platform_buy_fee_bps);
+            let referral_fee_lamports = bps_of(fee_lamports, referral_fee_bps);
+            let transfer_fee_amount = transfer_fee(&token.to_account_info(), token_amount)?;
+
+            if token_amount - transfer_fee_amount < minimum_amount_out {
+                msg!("buy returns {} tokens after transfer fees, expected at least {minimum_amount_out}", token_amount - transfer_fee_amount);
+                return Err(SlippageExceeded.into());
+            }
+            token_launch.assert_within_max_wallet(
+                ctx.accounts.user_token_account.amount + token_amount - transfer_fee_amount,
+                clock.unix_timestamp,
+            )?;
+
+            token_launch.apply_buy(lamport_amount, token_amount)?;
+
+            if token_launch.is_native_quote() {
+                for (to, amount) in [
+                    (ctx.accounts.sol_vault.to_account_info(), lamport_amount),
+                    (
+                        ctx.accounts.fee_wallet.to_account_info(),
+                        fee_lamports - referral_fee_lamports,
+                    ),
+                ]
+                .into_iter()
+                .chain(referrer_info.map(|referrer| (referrer, referral_fee_lamports)))
+                {
+                    system_program::transfer(
+                        CpiContext::new(
+                            ctx.accounts.system_program.to_account_info(),
+                            system_program::Transfer {
+                                from: user.to_account_info(),
+                                to,
+                            },
+                        ),
+                        amount,
+                    )?;
+                }
+            } else {
+                let (
+                    Some(quote_mint),
+                    Some(quote_vault),
+                    Some(user_quote_account),
+                    Some(fee_wallet_quote_account),
+                    Some(quote_token_program),
+                ) = (
+                    &ctx.accounts.quote_mint,
+                    &ctx.accounts.quote_vault,
+                    &ctx.accounts.user_quote_account,
+                    &ctx.accounts.fee_wallet_quote_account,
+                    &ctx.accounts.quote_token_program,
+                )
+                else {
+                    msg!("SPL-quoted launches need the quote vault and quote token accounts");
+                    return Err(MissingAccount.into());
+                };
+
+                for (to, amount) in [
+                    (quote_vault.to_account_info(), lamport_amount),
+                    (fee_wallet_quote_account.to_account_info(), fee_lamports),
+                ] {
+                    transfer_quote(
+                        quote_token_program,
+                        quote_mint,
+                        user_quote_account.to_account_info(),
+                        to,
+                        user.to_account_info(),
+                        &[],
+                        amount,
+                    )?;
+                }
+            }
+
+            anchor_spl::token_interface::transfer_checked(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    anchor_spl::token_interface::TransferChecked {
+                        from: ctx.accounts.launch_token_account.to_account_info(),
+                        mint: token.to_account_info(),
+                        to: ctx.accounts.user_token_account.to_account_info(),
+                        authority: token_launch.to_account_info(),
+                    },
+                    &[&[
+                        TOKEN_LAUNCH.as_bytes(),
+                        &token.key().to_bytes(),
+                        &[ctx.bumps.token_launch],
+                    ]],
+                ),
+                token_amount,
+                token.decimals,
+            )?;
+
+            if token_launch.lamports_to_graduation() == 0 {
+                token_launch.phase = LaunchPhase::Completed;
+            }
+            trader_state.last_buy_slot = Some(slot);
+
+            (
+                lamport_amount,
+                token_amount,
+                fee_lamports,
+                referral_fee_lamports,
+                0,
+                transfer_fee_amount,
+            )
+        }
+        SwapDirection::Sell => {
+            let penalty_bps = trader_state.sell_penalty_bps(config, slot)?;
+            let amount_in = match swap_amount {
+                SwapAmount::ExactIn { amount_in, .. } => amount_in,
+                // the curve must pay out `amount_out` after the platform fee and penalty
+                SwapAmount::ExactOut { amount_out, .. } => {
+                    let fee_bps = config.platform_sell_fee_bps as u64 + penalty_bps as u64;
+                    if fee_bps >= BPS_DENOMINATOR {
+                        msg!(&format!(
+                            "no sell returns lamports at fees of {fee_bps} bps"
+                        ));
+                        return Err(ValueInvalid.into());
+                    }
+                    let lamport_amount = ((amount_out as u128) * (BPS_DENOMINATOR as u128))
+                        .div_ceil((BPS_DENOMINATOR - fee_bps) as u128);
+                    let token_amount = token_launch.quote_tokens_for_lamports(
+                        u64::try_from(lamport_amount).map_err(|_| MathOverflow)?,
+                    )?;
+                    let amount_in =
+                        pre_transfer_fee_amount(&token.to_account_info(), token_amount)?;
+                    if amount_in > max_amount_in {
+                        msg!(&format!(
+                            "sell takes {amount_in} tokens, expected at most {max_amount_in}"
+                        ));
+                        return Err(SlippageExceeded.into());
+                    }
+                    amount_in
+                }
+            };
+
+            // the curve is only credited with what arrives after the transfer fee
+            let transfer_fee_amount = transfer_fee(&token.to_account_info(), amount_in)?;
+            let token_amount = amount_in - transfer_fee_amount;
+            let lamport_amount = token_launch.quote_sell(token_amount)?;
+            let fee_lamports = bps_of(lamport_amount, config.platform_sell_fee_bps);
+            let referral_fee_lamports = bps_of(fee_lamports, referral_fee_bps);
+            let penalty_lamports = bps_of(lamport_amount, penalty_bps);
+            let user_lamports = lamport_amount - fee_lamports - penalty_lamports;
+
+            if user_lamports < minimum_amount_out {
+                msg!(&format!(
+                    "sell returns {user_lamports} lamports, expected at least {minimum_amount_out}"
+                ));
+                return Err(SlippageExceeded.into());
+            }
+
+            token_launch.apply_sell(token_amount, lamport_amount)?;
+
+            anchor_spl::token_interface::transfer_checked(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    anchor_spl::token_interface::TransferChecked {
+                        from: ctx.accounts.user_token_account.to_account_info(),
+                        mint: token.to_account_info(),
+                        to: ctx.accounts.launch_token_account.to_account_info(),
+                        authority: user.to_account_info(),
+                    },
+                ),
+                amount_in,
+                token.decimals,
+            )?;
+
+            if token_launch.is_native_quote() {
+                for (to, amount) in [
+                    (user.to_account_info(), user_lamports),
+                    (
+                        ctx.accounts.fee_wallet.to_account_info(),
+                        fee_lamports - referral_fee_lamports,
+                    ),
+                    (ctx.accounts.creator.to_account_info(), penalty_lamports),
+                ]
+                .into_iter()
+                .chain(referrer_info.map(|referrer| (referrer, referral_fee_lamports)))
+                {
+                    system_program::transfer(
+                        CpiContext::new_with_signer(
+                            ctx.accounts.system_program.to_account_info(),
+                            system_program::Transfer {
+                                from: ctx.accounts.sol_vault.to_account_info(),
+                                to,
+                            },
+                            &[&[
+                                SOL_VAULT.as_bytes(),
+                                &token.key().to_bytes(),
+                                &[ctx.bumps.sol_vault],
+                            ]],
+                        ),
+                        amount,
+                    )?;
+                }
+            } else {
+                let (
+                    Some(quote_mint),
+                    Some(quote_vault),
+                    Some(user_quote_account),
+                    Some(fee_wallet_quote_account),
+                    Some(quote_token_program),
+                ) = (
+                    &ctx.accounts.quote_mint,
+                    &ctx.accounts.quote_vault,
+                    &ctx.accounts.user_quote_account,
+                    &ctx.accounts.fee_wallet_quote_account,
+                    &ctx.accounts.quote_token_program,
+                )
+                else {
+                    msg!("SPL-quoted launches need the quote vault and quote token accounts");
+                    return Err(MissingAccount.into());
+                };
+
+                let penalty = match (&ctx.accounts.creator_quote_account, penalty_lamports) {
+                    (_, 0) => None,
+                    (Some(creator_quote_account), penalty_lamports) => {
+                        Some((creator_quote_account.to_account_info(), penalty_lamports))
+                    }
+                    (None, _) => {
+                        msg!("sandwich penalties of SPL-quoted launches need the creator quote account");
+                        return Err(MissingAccount.into());
+                    }
+                };
+
+                for (to, amount) in [
+                    (user_quote_account.to_account_info(), user_lamports),
+                    (fee_wallet_quote_account.to_account_info(), fee_lamports),
+                ]
+                .into_iter()
+                .chain(penalty)
+                {
+                    transfer_quote(
+                        quote_token_program,
+                        quote_mint,
+                        quote_vault.to_account_info(),
+                        to,
+                        token_launch.to_account_info(),
+                        &[&[
+                            TOKEN_LAUNCH.as_bytes(),
+                            &token.key().to_bytes(),
+                            &[ctx.bumps.token_launch],
+                        ]],
+                        amount,
+                    )?;
+                }
+            }
+
+            (
+                lamport_amount,
+                token_amount,
+                fee_lamports,
+                referral_fee_lamports,
+                penalty_lamports,
+                transfer_fee_amount,
+            )
+        }
+    };
+
+    ctx.accounts.price_observations.record(token_launch);
+    ctx.accounts.launch_stats.record_trade(
+        token_launch,
+        direction,
+        lamport_amount,
+        clock.unix_timestamp,
+    )?;
+
+    if let Some(referrer) = &mut ctx.accounts.referrer {
+        referrer.record(lamport_amount, referral_fee_lamports)?;
+    }
+
+    emit!(Trade {
+        token: token.key(),
+        user: user.key(),
+        direction,
+        lamport_amount,
+        token_amount,
+        fee_lamports,
+        referral_fee_lamports,
+        penalty_lamports,
+        transfer_fee_amount,
+        virtual_lamport_reserves: token_launch.virtual_lamport_reserves,
+        virtual_token_reserves: token_launch.virtual_token_reserves,
+        real_lamport_reserves: token_launch.real_lamport_reserves,
+    });
+
+    Ok(())
+}
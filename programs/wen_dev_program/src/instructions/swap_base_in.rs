@@ -1,12 +1,20 @@
-use amm_anchor::SwapBaseIn;
+use crate::{
+    constants::{BPS_DENOMINATOR, CONFIG},
+    errors::*,
+    events::ProxySwap,
+    state::{Config, SwapAmount, SwapDirection},
+    utils::{amm_authority, assert_amm_accounts, bps_of, load_amm_keys, unpack_token_account},
+};
+use amm_anchor::{SwapBaseIn, SwapBaseOut};
 use anchor_lang::prelude::*;
-use anchor_spl::token::Token;
+use anchor_spl::token::{Mint, Token, TokenAccount};
 #[derive(Accounts, Clone)]
 pub struct ProxySwapBaseIn<'info> {
-    /// CHECK: Safe
+    /// CHECK: Safe. The AMM program
+    #[account(address = amm_anchor::ID)]
     pub amm_program: UncheckedAccount<'info>,
     /// CHECK: Safe. amm Account
-    #[account(mut)]
+    #[account(mut, owner = amm_anchor::ID)]
     pub amm: UncheckedAccount<'info>,
     /// CHECK: Safe. Amm authority Account
     #[account()]
@@ -54,6 +62,17 @@ pub struct ProxySwapBaseIn<'info> {
     pub user_source_owner: Signer<'info>,
     /// CHECK: Safe. The spl token program
     pub token_program: Program<'info, Token>,
+    #[account(seeds = [CONFIG.as_bytes()], bump)]
+    pub config: Box<Account<'info, Config>>,
+    /// quote side of the pool, charged the platform fee
+    pub quote_mint: Box<Account<'info, Mint>>,
+    /// receives platform fees
+    #[account(
+        mut,
+        associated_token::mint = quote_mint,
+        associated_token::authority = config.fee_wallet,
+    )]
+    pub fee_wallet_quote_account: Box<Account<'info, TokenAccount>>,
 }
 
 impl<'a, 'b, 'c, 'info> From<&mut ProxySwapBaseIn<'info>>
@@ -92,5 +111,228 @@ pub fn swap_base_in(
     amount_in: u64,
     minimum_amount_out: u64,
 ) -> Result<()> {
-    amm_anchor::swap_base_in(ctx.accounts.into(), amount_in, minimum_amount_out)
+    let cpi_ctx = (&mut *ctx.accounts).into();
+    swap_with_platform_fee(
+        cpi_ctx,
+        &ctx.accounts.config,
+        &ctx.accounts.quote_mint,
+        &ctx.accounts.fee_wallet_quote_account,
+        SwapAmount::ExactIn {
+            amount_in,
+            minimum_amount_out,
+        },
+    )
+}
+
+/// Swaps through the AMM, charging the platform fee on the quote side of the trade.
+///
+/// The side is found from the mints of the user's token accounts. Buys pay
+/// `Config.platform_buy_fee_bps` of the quote tokens spent on top, so `max_amount_in` only bounds
+/// what goes into the pool. Sells keep `Config.platform_sell_fee_bps` of the quote tokens
+/// received, `minimum_amount_out` and `amount_out` applying after the fee.
+pub(crate) fn swap_with_platform_fee<'info>(
+    cpi_ctx: CpiContext<'_, '_, '_, 'info, SwapBaseIn<'info>>,
+    config: &Config,
+    quote_mint: &Account<'info, Mint>,
+    fee_wallet_quote_account: &Account<'info, TokenAccount>,
+    swap_amount: SwapAmount,
+) -> Result<()> {
+    validate_swap_accounts(&cpi_ctx.program, &cpi_ctx.accounts)?;
+    // only allowed quote mints are charged in
+    config.quote_limits(&quote_mint.key())?;
+
+    let accounts = &cpi_ctx.accounts;
+    let (source_info, destination_info) = (
+        accounts.user_token_source.to_account_info(),
+        accounts.user_token_destination.to_account_info(),
+    );
+    let (amm, user, token_program) = (
+        accounts.amm.key(),
+        accounts.user_source_owner.to_account_info(),
+        accounts.token_program.to_account_info(),
+    );
+    let source = unpack_token_account(&source_info)?;
+    let destination = unpack_token_account(&destination_info)?;
+    let (direction, fee_bps) = if source.mint == quote_mint.key() {
+        (SwapDirection::Buy, config.platform_buy_fee_bps)
+    } else if destination.mint == quote_mint.key() {
+        (SwapDirection::Sell, config.platform_sell_fee_bps)
+    } else {
+        msg!("neither side of the swap trades {}", quote_mint.key());
+        return Err(IncorrectTokenAccount.into());
+    };
+
+    // sells ask the pool for enough to cover the fee
+    let pool_swap_amount = match (direction, swap_amount) {
+        (
+            SwapDirection::Sell,
+            SwapAmount::ExactIn {
+                amount_in,
+                minimum_amount_out,
+            },
+        ) => SwapAmount::ExactIn {
+            amount_in,
+            minimum_amount_out: pre_fee_amount(minimum_amount_out, fee_bps)?,
+        },
+        (
+            SwapDirection::Sell,
+            SwapAmount::ExactOut {
+                max_amount_in,
+                amount_out,
+            },
+        ) => SwapAmount::ExactOut {
+            max_amount_in,
+            amount_out: pre_fee_amount(amount_out, fee_bps)?,
+        },
+        (SwapDirection::Buy, swap_amount) => swap_amount,
+    };
+
+    match pool_swap_amount {
+        SwapAmount::ExactIn {
+            amount_in,
+            minimum_amount_out,
+        } => amm_anchor::swap_base_in(cpi_ctx, amount_in, minimum_amount_out)?,
+        SwapAmount::ExactOut {
+            max_amount_in,
+            amount_out,
+        } => {
+            let accounts = cpi_ctx.accounts;
+            amm_anchor::swap_base_out(
+                CpiContext::new(
+                    cpi_ctx.program,
+                    SwapBaseOut {
+                        amm: accounts.amm,
+                        amm_authority: accounts.amm_authority,
+                        amm_open_orders: accounts.amm_open_orders,
+                        amm_coin_vault: accounts.amm_coin_vault,
+                        amm_pc_vault: accounts.amm_pc_vault,
+                        market_program: accounts.market_program,
+                        market: accounts.market,
+                        market_bids: accounts.market_bids,
+                        market_asks: accounts.market_asks,
+                        market_event_queue: accounts.market_event_queue,
+                        market_coin_vault: accounts.market_coin_vault,
+                        market_pc_vault: accounts.market_pc_vault,
+                        market_vault_signer: accounts.market_vault_signer,
+                        user_token_source: accounts.user_token_source,
+                        user_token_destination: accounts.user_token_destination,
+                        user_source_owner: accounts.user_source_owner,
+                        token_program: accounts.token_program,
+                    },
+                ),
+                max_amount_in,
+                amount_out,
+            )?
+        }
+    }
+
+    let amount_in = source.amount - unpack_token_account(&source_info)?.amount;
+    let amount_out = unpack_token_account(&destination_info)?.amount - destination.amount;
+    let (quote_amount, token_amount, quote_account) = match direction {
+        SwapDirection::Buy => (amount_in, amount_out, source_info),
+        SwapDirection::Sell => (amount_out, amount_in, destination_info),
+    };
+    let fee_amount = bps_of(quote_amount, fee_bps);
+    let quote_amount_after_fee = match direction {
+        SwapDirection::Buy => quote_amount + fee_amount,
+        SwapDirection::Sell => quote_amount - fee_amount,
+    };
+
+    if direction == SwapDirection::Sell {
+        let minimum_amount_out = match swap_amount {
+            SwapAmount::ExactIn {
+                minimum_amount_out, ..
+            } => minimum_amount_out,
+            SwapAmount::ExactOut { amount_out, .. } => amount_out,
+        };
+        if quote_amount_after_fee < minimum_amount_out {
+            msg!(&format!("sell returns {quote_amount_after_fee} after the platform fee, expected at least {minimum_amount_out}"));
+            return Err(SlippageExceeded.into());
+        }
+    }
+
+    transfer_platform_fee(
+        token_program,
+        quote_account,
+        quote_mint,
+        fee_wallet_quote_account,
+        user.clone(),
+        fee_amount,
+    )?;
+
+    emit!(ProxySwap {
+        amm,
+        user: user.key(),
+        direction,
+        quote_amount_before_fee: quote_amount,
+        quote_amount_after_fee,
+        fee_amount,
+        token_amount,
+    });
+
+    Ok(())
+}
+
+/// Moves the platform fee from the user's `quote_account` to the fee wallet
+pub(crate) fn transfer_platform_fee<'info>(
+    token_program: AccountInfo<'info>,
+    quote_account: AccountInfo<'info>,
+    quote_mint: &Account<'info, Mint>,
+    fee_wallet_quote_account: &Account<'info, TokenAccount>,
+    user: AccountInfo<'info>,
+    fee_amount: u64,
+) -> Result<()> {
+    if fee_amount == 0 {
+        return Ok(());
+    }
+
+    anchor_spl::token::transfer_checked(
+        CpiContext::new(
+            token_program,
+            anchor_spl::token::TransferChecked {
+                from: quote_account,
+                mint: quote_mint.to_account_info(),
+                to: fee_wallet_quote_account.to_account_info(),
+                authority: user,
+            },
+        ),
+        fee_amount,
+        quote_mint.decimals,
+    )
+}
+
+/// Checks the AMM program and pool accounts of a swap against the pool's `AmmInfo`
+pub(crate) fn validate_swap_accounts(program: &AccountInfo, accounts: &SwapBaseIn) -> Result<()> {
+    if program.key() != amm_anchor::ID {
+        msg!("expected the AMM program {}", amm_anchor::ID);
+        return Err(IncorrectAmmAccount.into());
+    }
+
+    let amm_keys = load_amm_keys(&accounts.amm)?;
+    assert_amm_accounts(
+        &accounts.amm,
+        &[
+            (accounts.amm_authority.key(), amm_authority(&amm_keys)?),
+            (accounts.amm_open_orders.key(), amm_keys.open_orders),
+            (accounts.amm_coin_vault.key(), amm_keys.coin_vault),
+            (accounts.amm_pc_vault.key(), amm_keys.pc_vault),
+            (accounts.market_program.key(), amm_keys.market_program),
+            (accounts.market.key(), amm_keys.market),
+        ],
+    )
+}
+
+/// amount that is still `amount` once `fee_bps` of it is taken
+fn pre_fee_amount(amount: u64, fee_bps: u16) -> Result<u64> {
+    let fee_bps = fee_bps as u64;
+    if fee_bps >= BPS_DENOMINATOR {
+        msg!(&format!("nothing is left after a fee of {fee_bps} bps"));
+        return Err(ValueInvalid.into());
+    }
+
+    u64::try_from(
+        ((amount as u128) * (BPS_DENOMINATOR as u128))
+            .div_ceil((BPS_DENOMINATOR - fee_bps) as u128),
+    )
+    .map_err(|_| MathOverflow.into())
 }
@@ -1,12 +1,18 @@
-use amm_anchor::SwapBaseOut;
+use crate::{
+    constants::CONFIG,
+    instructions::swap_with_platform_fee,
+    state::{Config, SwapAmount},
+};
+use amm_anchor::SwapBaseIn;
 use anchor_lang::prelude::*;
-use anchor_spl::token::Token;
+use anchor_spl::token::{Mint, Token, TokenAccount};
 #[derive(Accounts, Clone)]
 pub struct ProxySwapBaseOut<'info> {
-    /// CHECK: Safe
+    /// CHECK: Safe. The AMM program
+    #[account(address = amm_anchor::ID)]
     pub amm_program: AccountInfo<'info>,
     /// CHECK: Safe. amm Account
-    #[account(mut)]
+    #[account(mut, owner = amm_anchor::ID)]
     pub amm: UncheckedAccount<'info>,
     /// CHECK: Safe. Amm authority Account
     #[account()]
@@ -54,15 +60,27 @@ pub struct ProxySwapBaseOut<'info> {
     pub user_source_owner: Signer<'info>,
     /// CHECK: Safe. The spl token program
     pub token_program: Program<'info, Token>,
+    #[account(seeds = [CONFIG.as_bytes()], bump)]
+    pub config: Box<Account<'info, Config>>,
+    /// quote side of the pool, charged the platform fee
+    pub quote_mint: Box<Account<'info, Mint>>,
+    /// receives platform fees
+    #[account(
+        mut,
+        associated_token::mint = quote_mint,
+        associated_token::authority = config.fee_wallet,
+    )]
+    pub fee_wallet_quote_account: Box<Account<'info, TokenAccount>>,
 }
 
+/// `swap_with_platform_fee` takes the `SwapBaseIn` accounts, which are the same as `SwapBaseOut`
 impl<'a, 'b, 'c, 'info> From<&mut ProxySwapBaseOut<'info>>
-    for CpiContext<'a, 'b, 'c, 'info, SwapBaseOut<'info>>
+    for CpiContext<'a, 'b, 'c, 'info, SwapBaseIn<'info>>
 {
     fn from(
         accounts: &mut ProxySwapBaseOut<'info>,
-    ) -> CpiContext<'a, 'b, 'c, 'info, SwapBaseOut<'info>> {
-        let cpi_accounts = SwapBaseOut {
+    ) -> CpiContext<'a, 'b, 'c, 'info, SwapBaseIn<'info>> {
+        let cpi_accounts = SwapBaseIn {
             amm: accounts.amm.clone(),
             amm_authority: accounts.amm_authority.clone(),
             amm_open_orders: accounts.amm_open_orders.clone(),
@@ -92,5 +110,15 @@ pub fn swap_base_out(
     max_amount_in: u64,
     amount_out: u64,
 ) -> Result<()> {
-    amm_anchor::swap_base_out(ctx.accounts.into(), max_amount_in, amount_out)
+    let cpi_ctx = (&mut *ctx.accounts).into();
+    swap_with_platform_fee(
+        cpi_ctx,
+        &ctx.accounts.config,
+        &ctx.accounts.quote_mint,
+        &ctx.accounts.fee_wallet_quote_account,
+        SwapAmount::ExactOut {
+            max_amount_in,
+            amount_out,
+        },
+    )
 }
@@ -0,0 +1,180 @@
+use crate::{
+    constants::{METADATA, TOKEN_LAUNCH},
+    errors::*,
+    state::{LaunchPhase, TokenLaunch, TokenStandard},
+    utils,
+};
+use anchor_lang::{prelude::*, solana_program::program::invoke_signed, system_program};
+use anchor_spl::{
+    metadata::{
+        mpl_token_metadata::{accounts::Metadata as MetadataAccount, types::DataV2},
+        Metadata,
+    },
+    token_2022::spl_token_2022::{
+        self,
+        extension::{BaseStateWithExtensions, StateWithExtensions},
+    },
+    token_interface::{Mint, TokenInterface},
+};
+use spl_token_metadata_interface::state::{Field, TokenMetadata};
+
+#[derive(Accounts)]
+pub struct UpdateLaunchMetadata<'info> {
+    #[account(mut)]
+    creator: Signer<'info>,
+
+    #[account(mut, mint::token_program = token_program)]
+    token: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        seeds = [TOKEN_LAUNCH.as_bytes(), &token.key().to_bytes()],
+        bump,
+        has_one = creator @ IncorrectAuthority,
+    )]
+    token_launch: Box<Account<'info, TokenLaunch>>,
+
+    /// CHECK: passed to token metadata program, only for `TokenStandard::SplToken`
+    #[account(
+        mut,
+        seeds = [
+            METADATA.as_bytes(),
+            &anchor_spl::metadata::ID.to_bytes(),
+            &token.key().to_bytes(),
+        ],
+        bump,
+        seeds::program = anchor_spl::metadata::ID
+    )]
+    token_metadata_account: Option<UncheckedAccount<'info>>,
+
+    #[account(address = system_program::ID)]
+    system_program: Program<'info, System>,
+
+    token_program: Interface<'info, TokenInterface>,
+
+    /// only for `TokenStandard::SplToken`
+    #[account(address = anchor_spl::metadata::ID)]
+    mpl_token_metadata_program: Option<Program<'info, Metadata>>,
+}
+
+/// Replaces the name, symbol and uri of a launch's token until it is migrated
+pub fn update_launch_metadata<'info>(
+    ctx: Context<'_, '_, '_, 'info, UpdateLaunchMetadata<'info>>,
+    name: String,
+    symbol: String,
+    uri: String,
+) -> Result<()> {
+    let creator = &ctx.accounts.creator;
+    let token = &ctx.accounts.token;
+    let token_launch = &ctx.accounts.token_launch;
+
+    match token_launch.phase {
+        LaunchPhase::Migrated | LaunchPhase::Cancelled => {
+            msg!(
+                "metadata of {:?} launches can no longer be updated",
+                token_launch.phase
+            );
+            return Err(IncorrectLaunchPhase.into());
+        }
+        _ => {}
+    }
+    utils::validate_metadata(&name, &symbol, &uri)?;
+
+    let token_launch_seeds: &[&[&[u8]]] = &[&[
+        TOKEN_LAUNCH.as_bytes(),
+        &token.key().to_bytes(),
+        &[ctx.bumps.token_launch],
+    ]];
+
+    match token_launch.token_standard {
+        TokenStandard::SplToken => {
+            let (Some(token_metadata_account), Some(mpl_token_metadata_program)) = (
+                &ctx.accounts.token_metadata_account,
+                &ctx.accounts.mpl_token_metadata_program,
+            ) else {
+                msg!("SplToken launches need the token metadata account and program");
+                return Err(MissingAccount.into());
+            };
+
+            // keep the verified creator and platform collection set at launch
+            let current = MetadataAccount::try_from(&token_metadata_account.to_account_info())
+                .map_err(|_| SerializationFailed)?;
+
+            anchor_spl::metadata::update_metadata_accounts_v2(
+                CpiContext::new_with_signer(
+                    mpl_token_metadata_program.to_account_info(),
+                    anchor_spl::metadata::UpdateMetadataAccountsV2 {
+                        metadata: token_metadata_account.to_account_info(),
+                        update_authority: token_launch.to_account_info(),
+                    },
+                    token_launch_seeds,
+                ),
+                None,
+                Some(DataV2 {
+                    name,
+                    symbol,
+                    uri,
+                    seller_fee_basis_points: current.seller_fee_basis_points,
+                    creators: current.creators,
+                    collection: current.collection,
+                    uses: current.uses,
+                }),
+                None,
+                None,
+            )?;
+        }
+        TokenStandard::Token2022 => {
+            // token-metadata reallocs the mint for longer fields, so fund the growth up front
+            let token_info = token.to_account_info();
+            let (data_len, size_increase) = {
+                let mint_data = token_info.try_borrow_data()?;
+                let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data)?;
+                let current = mint.get_variable_len_extension::<TokenMetadata>()?;
+                let mut updated = current.clone();
+                updated.update(Field::Name, name.clone());
+                updated.update(Field::Symbol, symbol.clone());
+                updated.update(Field::Uri, uri.clone());
+                (
+                    mint_data.len(),
+                    updated
+                        .tlv_size_of()?
+                        .saturating_sub(current.tlv_size_of()?),
+                )
+            };
+            let rent_top_up = Rent::get()?
+                .minimum_balance(data_len + size_increase)
+                .saturating_sub(token_info.lamports());
+            if rent_top_up > 0 {
+                system_program::transfer(
+                    CpiContext::new(
+                        ctx.accounts.system_program.to_account_info(),
+                        system_program::Transfer {
+                            from: creator.to_account_info(),
+                            to: token_info.clone(),
+                        },
+                    ),
+                    rent_top_up,
+                )?;
+            }
+
+            for (field, value) in [
+                (Field::Name, name),
+                (Field::Symbol, symbol),
+                (Field::Uri, uri),
+            ] {
+                invoke_signed(
+                    &spl_token_metadata_interface::instruction::update_field(
+                        &ctx.accounts.token_program.key(),
+                        &token.key(),
+                        &token_launch.key(),
+                        field,
+                        value,
+                    ),
+                    &[token_info.clone(), token_launch.to_account_info()],
+                    token_launch_seeds,
+                )?;
+            }
+        }
+    }
+
+    Ok(())
+}
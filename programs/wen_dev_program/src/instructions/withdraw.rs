@@ -1,12 +1,14 @@
+use crate::utils::{amm_authority, assert_amm_accounts, load_amm_keys};
 use amm_anchor::Withdraw;
 use anchor_lang::prelude::*;
 use anchor_spl::token::Token;
 #[derive(Accounts, Clone)]
 pub struct ProxyWithdraw<'info> {
-    /// CHECK: Safe
+    /// CHECK: Safe. The AMM program
+    #[account(address = amm_anchor::ID)]
     pub amm_program: UncheckedAccount<'info>,
     /// CHECK: Safe. Amm account
-    #[account(mut)]
+    #[account(mut, owner = amm_anchor::ID)]
     pub amm: UncheckedAccount<'info>,
     /// CHECK: Safe. Amm authority Account
     #[account()]
@@ -95,7 +97,28 @@ impl<'a, 'b, 'c, 'info> From<&mut ProxyWithdraw<'info>>
     }
 }
 
+impl ProxyWithdraw<'_> {
+    /// checks the pool accounts against the pool's `AmmInfo`
+    fn validate(&self) -> Result<()> {
+        let amm_keys = load_amm_keys(&self.amm)?;
+        assert_amm_accounts(
+            &self.amm,
+            &[
+                (self.amm_authority.key(), amm_authority(&amm_keys)?),
+                (self.amm_open_orders.key(), amm_keys.open_orders),
+                (self.amm_target_orders.key(), amm_keys.target_orders),
+                (self.amm_lp_mint.key(), amm_keys.lp_mint),
+                (self.amm_coin_vault.key(), amm_keys.coin_vault),
+                (self.amm_pc_vault.key(), amm_keys.pc_vault),
+                (self.market_program.key(), amm_keys.market_program),
+                (self.market.key(), amm_keys.market),
+            ],
+        )
+    }
+}
+
 /// withdraw instruction
 pub fn withdraw(ctx: Context<ProxyWithdraw>, amount: u64) -> Result<()> {
+    ctx.accounts.validate()?;
     amm_anchor::withdraw(ctx.accounts.into(), amount)
 }
@@ -1,7 +1,9 @@
 pub mod constants;
 pub mod errors;
+pub mod events;
 pub mod instructions;
 pub mod state;
+pub mod utils;
 
 use anchor_lang::prelude::*;
 
@@ -59,6 +61,15 @@ pub mod project_on_chain {
         instructions::swap_base_out(ctx, max_amount_in, amount_out)
     }
 
+    /// multi-hop swap_base_in instruction
+    pub fn proxy_route_swap<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ProxyRouteSwap<'info>>,
+        amount_in: u64,
+        minimum_amount_out: u64,
+    ) -> Result<()> {
+        instructions::route_swap_base_in(ctx, amount_in, minimum_amount_out)
+    }
+
     pub fn configure<'info>(
         ctx: Context<'_, '_, '_, 'info, Configure<'info>>,
         new_config: Config,
@@ -66,23 +77,46 @@ pub mod project_on_chain {
         instructions::configure(ctx, new_config)
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn launch<'info>(
         ctx: Context<'_, '_, '_, 'info, Launch<'info>>,
         name: String,
         symbol: String,
         uri: String,
+        verify_creator: bool,
+        token_standard: TokenStandard,
+        transfer_fee: Option<LaunchTransferFee>,
+        curve_kind: CurveKind,
         virtual_lamport_reserves: u64,
         token_supply: u64,
         decimals: u8,
+        allocation_mode: AllocationMode,
+        presale_token_cap: u64,
+        vesting: Option<VestingConfig>,
+        presale_start: i64,
+        presale_end: i64,
+        dev_buy_lamports: Option<u64>,
+        max_wallet: Option<MaxWalletLimit>,
     ) -> Result<()> {
         instructions::launch(
             ctx,
             name,
             symbol,
             uri,
+            verify_creator,
+            token_standard,
+            transfer_fee,
+            curve_kind,
             virtual_lamport_reserves,
             token_supply,
             decimals,
+            allocation_mode,
+            presale_token_cap,
+            vesting,
+            presale_start,
+            presale_end,
+            dev_buy_lamports,
+            max_wallet,
         )
     }
 
@@ -95,9 +129,155 @@ pub mod project_on_chain {
         instructions::snipe(ctx, token, bid_amount, buy_lamports)
     }
 
+    pub fn close_presale<'info>(
+        ctx: Context<'_, '_, '_, 'info, ClosePresale<'info>>,
+        token: Pubkey,
+    ) -> Result<()> {
+        instructions::close_presale(ctx, token)
+    }
+
+    pub fn process_snipes<'info>(
+        ctx: Context<'_, '_, '_, 'info, ProcessSnipes<'info>>,
+    ) -> Result<()> {
+        instructions::process_snipes(ctx)
+    }
+
+    pub fn open_sale<'info>(
+        ctx: Context<'_, '_, '_, 'info, OpenSale<'info>>,
+        token: Pubkey,
+    ) -> Result<()> {
+        instructions::open_sale(ctx, token)
+    }
+
+    pub fn swap<'info>(
+        ctx: Context<'_, '_, '_, 'info, Swap<'info>>,
+        direction: SwapDirection,
+        amount_in: u64,
+        minimum_amount_out: u64,
+        deadline: Option<SwapDeadline>,
+    ) -> Result<()> {
+        instructions::swap(ctx, direction, amount_in, minimum_amount_out, deadline)
+    }
+
+    pub fn swap_exact_out<'info>(
+        ctx: Context<'_, '_, '_, 'info, Swap<'info>>,
+        direction: SwapDirection,
+        max_amount_in: u64,
+        amount_out: u64,
+        deadline: Option<SwapDeadline>,
+    ) -> Result<()> {
+        instructions::swap_exact_out(ctx, direction, max_amount_in, amount_out, deadline)
+    }
+
+    pub fn route_swap<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RouteSwap<'info>>,
+        direction: SwapDirection,
+        swap_amount: SwapAmount,
+        deadline: Option<SwapDeadline>,
+    ) -> Result<()> {
+        instructions::route_swap(ctx, direction, swap_amount, deadline)
+    }
+
+    pub fn harvest_transfer_fees<'info>(
+        ctx: Context<'_, '_, '_, 'info, HarvestTransferFees<'info>>,
+    ) -> Result<()> {
+        instructions::harvest_transfer_fees(ctx)
+    }
+
+    pub fn update_launch_metadata<'info>(
+        ctx: Context<'_, '_, '_, 'info, UpdateLaunchMetadata<'info>>,
+        name: String,
+        symbol: String,
+        uri: String,
+    ) -> Result<()> {
+        instructions::update_launch_metadata(ctx, name, symbol, uri)
+    }
+
+    pub fn claim_vested<'info>(
+        ctx: Context<'_, '_, '_, 'info, ClaimVested<'info>>,
+    ) -> Result<()> {
+        instructions::claim_vested(ctx)
+    }
+
+    pub fn cancel_launch<'info>(
+        ctx: Context<'_, '_, '_, 'info, CancelLaunch<'info>>,
+    ) -> Result<()> {
+        instructions::cancel_launch(ctx)
+    }
+
+    pub fn expire_launch<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExpireLaunch<'info>>,
+    ) -> Result<()> {
+        instructions::expire_launch(ctx)
+    }
+
+    pub fn reclaim_snipe<'info>(
+        ctx: Context<'_, '_, '_, 'info, ReclaimSnipe<'info>>,
+        token: Pubkey,
+    ) -> Result<()> {
+        instructions::reclaim_snipe(ctx, token)
+    }
+
+    pub fn reclaim_dev_buy<'info>(
+        ctx: Context<'_, '_, '_, 'info, ReclaimDevBuy<'info>>,
+    ) -> Result<()> {
+        instructions::reclaim_dev_buy(ctx)
+    }
+
+    pub fn register_referrer<'info>(
+        ctx: Context<'_, '_, '_, 'info, RegisterReferrer<'info>>,
+    ) -> Result<()> {
+        instructions::register_referrer(ctx)
+    }
+
+    pub fn claim_referral_fees<'info>(
+        ctx: Context<'_, '_, '_, 'info, ClaimReferralFees<'info>>,
+    ) -> Result<()> {
+        instructions::claim_referral_fees(ctx)
+    }
+
+    pub fn place_curve_order<'info>(
+        ctx: Context<'_, '_, '_, 'info, PlaceCurveOrder<'info>>,
+        order_id: u64,
+        direction: SwapDirection,
+        amount_in: u64,
+        limit_amount_out: u64,
+    ) -> Result<()> {
+        instructions::place_curve_order(ctx, order_id, direction, amount_in, limit_amount_out)
+    }
+
+    pub fn cancel_curve_order<'info>(
+        ctx: Context<'_, '_, '_, 'info, CancelCurveOrder<'info>>,
+    ) -> Result<()> {
+        instructions::cancel_curve_order(ctx)
+    }
+
+    pub fn fill_curve_orders<'info>(
+        ctx: Context<'_, '_, 'info, 'info, FillCurveOrders<'info>>,
+    ) -> Result<()> {
+        instructions::fill_curve_orders(ctx)
+    }
+
+    pub fn get_twap(ctx: Context<GetTwap>, window_seconds: u32) -> Result<u128> {
+        instructions::get_twap(ctx, window_seconds)
+    }
+
     pub fn migrate<'info>(
         ctx: Context<'_, '_, '_, 'info, Migrate<'info>>
     ) -> Result<()> {
         instructions::migrate(ctx)
     }
+
+    pub fn close_launch<'info>(
+        ctx: Context<'_, '_, '_, 'info, CloseLaunch<'info>>,
+    ) -> Result<()> {
+        instructions::close_launch(ctx)
+    }
+
+    pub fn close_trader_state<'info>(
+        ctx: Context<'_, '_, '_, 'info, CloseTraderState<'info>>,
+        token: Pubkey,
+    ) -> Result<()> {
+        instructions::close_trader_state(ctx, token)
+    }
 }
@@ -1,4 +1,8 @@
-use crate::errors::*;
+use crate::{
+    constants::{BPS_DENOMINATOR, NATIVE_MINT},
+    errors::*,
+    utils::{bps_of, exp2_q63, LN_2_Q63, Q63_ONE},
+};
 use anchor_lang::{prelude::*, AnchorDeserialize, AnchorSerialize};
 use core::{cmp::Ordering, fmt::Debug};
 
@@ -11,10 +15,119 @@ pub struct Config {
     pub platform_sell_fee_bps: u16,
     pub pegasus_buy_fee_bps: u16,
     pub pegasus_sell_fee_bps: u16,
+    /// share of a filled sniper's `bid_amount` paid to the launch creator, the rest goes to `fee_wallet`
+    pub bid_creator_share_bps: u16,
+    /// seconds after `presale_end` when anyone may expire a launch still in `Presale` or
+    /// `ProcessingPresale`
+    pub launch_expiry_seconds: i64,
+    /// lamports the bonding curve must collect before the launch is `Completed`
+    pub graduation_lamports: u64,
+    /// share of a launch's token supply the creator may buy with `dev_buy_lamports`
+    pub dev_buy_max_bps: u16,
+    /// share of the platform fee paid to the referrer of a trade or snipe
+    pub referral_fee_bps: u16,
+    /// launches a creator may have in `Presale` at once, 0 for no limit
+    pub max_presales_per_creator: u16,
+    /// what happens to a launch's metadata update authority at `migrate`
+    pub metadata_migration_policy: MetadataMigrationPolicy,
+    /// sized Metaplex collection NFT grouping every `SplToken` launch, its collection authority
+    /// must be delegated to the `Config` PDA
+    pub collection_mint: Option<Pubkey>,
+    /// lamports escrowed with every curve order and paid to whoever fills it
+    pub curve_order_tip_lamports: u64,
+    /// slots after a wallet's curve buy in which its sells fall under `sandwich_policy`, 0 for
+    /// the same slot only
+    pub sandwich_window_slots: u64,
+    pub sandwich_policy: SandwichPolicy,
 
     pub lamport_amount_config: AmountConfig<u64>,
     pub token_supply_config: AmountConfig<u64>,
     pub token_decimals_config: AmountConfig<u8>,
+    /// seconds between `launch` and `presale_start`
+    pub presale_delay_config: AmountConfig<i64>,
+    /// seconds between `presale_start` and `presale_end`
+    pub presale_duration_config: AmountConfig<i64>,
+    /// basis points of the Token-2022 transfer fee a launch may set
+    pub transfer_fee_bps_config: AmountConfig<u16>,
+    /// price curves a launch may trade on
+    pub curve_kind_config: AmountConfig<CurveKind>,
+    /// share of the token supply a launch may cap wallets at early in `OpenSale`
+    pub max_wallet_bps_config: AmountConfig<u16>,
+    /// seconds into `OpenSale` a launch may keep its wallet cap
+    pub max_wallet_duration_config: AmountConfig<i64>,
+    /// SPL tokens a launch may be priced in instead of lamports
+    pub quote_mints: Vec<QuoteMintConfig>,
+}
+
+impl Config {
+    pub fn validate(&self) -> Result<()> {
+        if self.graduation_lamports == 0 {
+            msg!("graduation_lamports must be positive");
+            return Err(ValueTooSmall.into());
+        }
+        if self.launch_expiry_seconds <= 0 {
+            msg!("launch_expiry_seconds must be positive");
+            return Err(ValueTooSmall.into());
+        }
+        for (name, bps) in [
+            ("platform_buy_fee_bps", self.platform_buy_fee_bps),
+            ("platform_sell_fee_bps", self.platform_sell_fee_bps),
+            ("bid_creator_share_bps", self.bid_creator_share_bps),
+            ("dev_buy_max_bps", self.dev_buy_max_bps),
+            ("referral_fee_bps", self.referral_fee_bps),
+        ] {
+            if bps as u64 > BPS_DENOMINATOR {
+                msg!(&format!("{name} {bps} exceeds {BPS_DENOMINATOR}"));
+                return Err(ValueTooLarge.into());
+            }
+        }
+        if let SandwichPolicy::Penalty { fee_bps } = self.sandwich_policy {
+            if self.platform_sell_fee_bps as u64 + fee_bps as u64 > BPS_DENOMINATOR {
+                msg!("sandwich penalty {fee_bps} and platform_sell_fee_bps {} exceed {BPS_DENOMINATOR}", self.platform_sell_fee_bps);
+                return Err(ValueTooLarge.into());
+            }
+        }
+        for quote_mint in &self.quote_mints {
+            if quote_mint.mint == NATIVE_MINT {
+                msg!("lamport launches are configured by graduation_lamports and lamport_amount_config");
+                return Err(ValueInvalid.into());
+            }
+            if quote_mint.graduation_amount == 0 {
+                msg!("graduation_amount of {} must be positive", quote_mint.mint);
+                return Err(ValueTooSmall.into());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// graduation target and allowed starting virtual reserves for launches priced in `quote_mint`
+    pub fn quote_limits(&self, quote_mint: &Pubkey) -> Result<(u64, &AmountConfig<u64>)> {
+        if *quote_mint == NATIVE_MINT {
+            return Ok((self.graduation_lamports, &self.lamport_amount_config));
+        }
+
+        match self
+            .quote_mints
+            .iter()
+            .find(|config| config.mint == *quote_mint)
+        {
+            Some(config) => Ok((config.graduation_amount, &config.virtual_reserves_config)),
+            None => {
+                msg!(&format!("{quote_mint} is not an allowed quote mint"));
+                Err(ValueInvalid.into())
+            }
+        }
+    }
+}
+
+/// Limits for launches priced in an SPL token, amounts are in base units of `mint`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub struct QuoteMintConfig {
+    pub mint: Pubkey,
+    /// amount the bonding curve must collect before the launch is `Completed`
+    pub graduation_amount: u64,
+    pub virtual_reserves_config: AmountConfig<u64>,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
@@ -56,17 +169,665 @@ impl<T: PartialEq + PartialOrd + Debug> AmountConfig<T> {
 
 #[account]
 pub struct TokenLaunch {
+    /// sequential id handed out by the `LaunchRegistry`
+    pub launch_id: u64,
     pub token: Pubkey,
+    pub token_standard: TokenStandard,
+    pub transfer_fee: Option<LaunchTransferFee>,
+    pub curve_kind: CurveKind,
+    /// mint the curve is priced in, `NATIVE_MINT` for lamports held in the sol vault,
+    /// any other mint is held in the launch's associated token account for it
+    pub quote_mint: Pubkey,
     pub creator: Pubkey,
     pub phase: LaunchPhase,
+    pub created_at: i64,
+    pub presale_start: i64,
+    pub presale_end: i64,
 
+    /// `lamport` amounts of SPL-quoted launches are in base units of `quote_mint`
     pub virtual_lamport_reserves: u64,
     pub virtual_token_reserves: u64,
     pub initial_token_max_supply: u64,
+    pub initial_virtual_lamport_reserves: u64,
+    /// lamports actually collected by the curve, presale fills included
+    pub real_lamport_reserves: u64,
+    pub graduation_lamports: u64,
+
+    pub allocation_mode: AllocationMode,
+    pub presale_token_cap: u64,
+
+    pub vesting: Option<VestingConfig>,
+
+    /// lamports the creator spent on the curve at launch
+    pub dev_buy_lamports: u64,
+    /// tokens the creator received at launch
+    pub dev_buy_token_amount: u64,
+
+    /// running sum of the spot price, in Q64 lamports per token base unit, times the seconds it
+    /// held, wraps on overflow so only differences are meaningful
+    pub price_cumulative: u128,
+    pub price_updated_at: i64,
+
+    /// cap on curve buys early in `OpenSale`
+    pub max_wallet: Option<MaxWalletLimit>,
+    /// when the launch entered `OpenSale`
+    pub sale_opened_at: i64,
+    /// when `close_presale` settled the presale, the start of every `VestingSchedule`
+    pub presale_settled_at: i64,
 }
 
 impl TokenLaunch {
-    pub const ACCOUNT_LEN: usize = 8 + 32 + 32 + 1 + 8 + 8 + 8;
+    pub const ACCOUNT_LEN: usize = 8
+        + 8
+        + 32
+        + 1
+        + 1
+        + LaunchTransferFee::DATA_LEN
+        + CurveKind::MAX_LEN
+        + 32
+        + 32
+        + 1
+        + 8
+        + 8
+        + 8
+        + 8
+        + 8
+        + 8
+        + 8
+        + 8
+        + 8
+        + AllocationMode::MAX_LEN
+        + 8
+        + 1
+        + VestingConfig::DATA_LEN
+        + 8
+        + 8
+        + 16
+        + 8
+        + 1
+        + MaxWalletLimit::DATA_LEN
+        + 8
+        + 8;
+
+    /// whether the curve is priced in lamports rather than an SPL token
+    pub fn is_native_quote(&self) -> bool {
+        self.quote_mint == NATIVE_MINT
+    }
+
+    pub fn assert_presale_open(&self, now: i64) -> Result<()> {
+        if now < self.presale_start || now >= self.presale_end {
+            msg!(
+                "presale is open from {} until {}, now is {now}",
+                self.presale_start,
+                self.presale_end
+            );
+            return Err(PresaleNotOpen.into());
+        }
+
+        Ok(())
+    }
+
+    pub fn assert_presale_ended(&self, now: i64) -> Result<()> {
+        if now < self.presale_end {
+            msg!("presale ends at {}, now is {now}", self.presale_end);
+            return Err(PresaleNotEnded.into());
+        }
+
+        Ok(())
+    }
+
+    /// tokens received for `lamports` at the current point of the curve
+    pub fn quote_buy(&self, lamports: u64) -> Result<u64> {
+        if lamports == 0 {
+            return Ok(0);
+        }
+        if self.curve_kind != CurveKind::ConstantProduct {
+            // largest amount of tokens whose price fits in `lamports`
+            let sold = self.tokens_sold();
+            let budget = self.curve_lamports(sold)?.saturating_add(lamports);
+            return last_fitting(0, self.virtual_token_reserves.saturating_sub(1), |tokens| {
+                Ok(matches!(self.curve_lamports(sold + tokens), Ok(lamports) if lamports <= budget))
+            });
+        }
+
+        let tokens = (self.virtual_token_reserves as u128) * (lamports as u128)
+            / (self.virtual_lamport_reserves as u128 + lamports as u128);
+
+        u64::try_from(tokens).map_err(|_| MathOverflow.into())
+    }
+
+    /// lamports required to buy exactly `tokens` at the current point of the curve, rounded up
+    pub fn quote_lamports_for_tokens(&self, tokens: u64) -> Result<u64> {
+        if tokens >= self.virtual_token_reserves {
+            msg!(
+                "cannot buy {tokens} tokens, only {} in virtual reserves",
+                self.virtual_token_reserves
+            );
+            return Err(InsufficientReserves.into());
+        }
+
+        if self.curve_kind != CurveKind::ConstantProduct {
+            let sold = self.tokens_sold();
+            return Ok(self.curve_lamports(sold + tokens)? - self.curve_lamports(sold)?);
+        }
+
+        let numerator = (self.virtual_lamport_reserves as u128) * (tokens as u128);
+        let denominator = (self.virtual_token_reserves - tokens) as u128;
+        let lamports = numerator.div_ceil(denominator);
+
+        u64::try_from(lamports).map_err(|_| MathOverflow.into())
+    }
+
+    /// lamports received for selling `tokens` at the current point of the curve
+    pub fn quote_sell(&self, tokens: u64) -> Result<u64> {
+        if self.curve_kind != CurveKind::ConstantProduct {
+            let sold = self.tokens_sold();
+            if tokens > sold {
+                msg!(&format!(
+                    "cannot sell {tokens} tokens, only {sold} sold by the curve"
+                ));
+                return Err(InsufficientReserves.into());
+            }
+            return Ok(self.curve_lamports(sold)? - self.curve_lamports(sold - tokens)?);
+        }
+
+        let lamports = (self.virtual_lamport_reserves as u128) * (tokens as u128)
+            / (self.virtual_token_reserves as u128 + tokens as u128);
+
+        u64::try_from(lamports).map_err(|_| MathOverflow.into())
+    }
+
+    /// tokens the curve must receive to pay out at least `lamports` at the current point of the
+    /// curve, rounded up
+    pub fn quote_tokens_for_lamports(&self, lamports: u64) -> Result<u64> {
+        if self.curve_kind != CurveKind::ConstantProduct {
+            // smallest amount of tokens whose sale covers `lamports`
+            let sold = self.tokens_sold();
+            let curve_lamports = self.curve_lamports(sold)?;
+            if lamports > curve_lamports {
+                msg!(&format!(
+                    "cannot sell for {lamports} lamports, only {curve_lamports} in the curve"
+                ));
+                return Err(InsufficientReserves.into());
+            }
+            let floor = curve_lamports - lamports;
+            return first_covering(0, sold, |tokens| {
+                Ok(self.curve_lamports(sold - tokens)? <= floor)
+            });
+        }
+
+        if lamports >= self.virtual_lamport_reserves {
+            msg!(
+                "cannot sell for {lamports} lamports, only {} in virtual reserves",
+                self.virtual_lamport_reserves
+            );
+            return Err(InsufficientReserves.into());
+        }
+
+        let numerator = (lamports as u128) * (self.virtual_token_reserves as u128);
+        let denominator = (self.virtual_lamport_reserves - lamports) as u128;
+        let tokens = numerator.div_ceil(denominator);
+
+        u64::try_from(tokens).map_err(|_| MathOverflow.into())
+    }
+
+    fn tokens_sold(&self) -> u64 {
+        self.initial_token_max_supply - self.virtual_token_reserves
+    }
+
+    fn curve_lamports(&self, sold: u64) -> Result<u64> {
+        self.curve_kind.lamports_for_sold(
+            sold,
+            self.initial_token_max_supply,
+            self.initial_virtual_lamport_reserves,
+        )
+    }
+
+    /// marginal price of the curve in Q64 lamports per token base unit
+    pub fn spot_price_q64(&self) -> Result<u128> {
+        if self.curve_kind == CurveKind::ConstantProduct {
+            return Ok(((self.virtual_lamport_reserves as u128) << 64)
+                / self.virtual_token_reserves as u128);
+        }
+
+        self.curve_kind.spot_price_q64(
+            self.tokens_sold(),
+            self.initial_token_max_supply,
+            self.initial_virtual_lamport_reserves,
+        )
+    }
+
+    /// `price_cumulative` as of `now`, without updating it
+    pub fn price_cumulative_at(&self, now: i64) -> Result<u128> {
+        let elapsed = now.saturating_sub(self.price_updated_at).max(0) as u128;
+        Ok(self
+            .price_cumulative
+            .wrapping_add(self.spot_price_q64()?.wrapping_mul(elapsed)))
+    }
+
+    /// adds the current price to `price_cumulative` for the time since it was last updated, called
+    /// before every move of the reserves
+    fn accrue_price(&mut self) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        self.price_cumulative = self.price_cumulative_at(now)?;
+        self.price_updated_at = now;
+
+        Ok(())
+    }
+
+    /// most tokens a wallet may hold after a curve buy at `now`, if the wallet cap still applies
+    pub fn max_wallet_amount(&self, now: i64) -> Option<u64> {
+        match &self.max_wallet {
+            Some(max_wallet)
+                if self.phase == LaunchPhase::OpenSale
+                    && now
+                        < self
+                            .sale_opened_at
+                            .saturating_add(max_wallet.duration_seconds) =>
+            {
+                Some(bps_of(self.initial_token_max_supply, max_wallet.bps))
+            }
+            _ => None,
+        }
+    }
+
+    /// fails if a curve buy leaving a wallet with `balance` tokens breaks the wallet cap
+    pub fn assert_within_max_wallet(&self, balance: u64, now: i64) -> Result<()> {
+        match self.max_wallet_amount(now) {
+            Some(max_amount) if balance > max_amount => {
+                msg!("buy leaves the wallet with {balance} tokens, at most {max_amount} allowed until {}", self.sale_opened_at
+                        + self
+                            .max_wallet
+                            .map_or(0, |max_wallet| max_wallet.duration_seconds));
+                Err(MaxWalletExceeded.into())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// lamports still needed before the curve graduates
+    pub fn lamports_to_graduation(&self) -> u64 {
+        self.graduation_lamports
+            .saturating_sub(self.real_lamport_reserves)
+    }
+
+    /// moves the reserves along the curve after a buy
+    pub fn apply_buy(&mut self, lamports: u64, tokens: u64) -> Result<()> {
+        self.accrue_price()?;
+        self.virtual_lamport_reserves = self
+            .virtual_lamport_reserves
+            .checked_add(lamports)
+            .ok_or(MathOverflow)?;
+        self.virtual_token_reserves = self
+            .virtual_token_reserves
+            .checked_sub(tokens)
+            .ok_or(InsufficientReserves)?;
+        self.real_lamport_reserves = self
+            .real_lamport_reserves
+            .checked_add(lamports)
+            .ok_or(MathOverflow)?;
+
+        Ok(())
+    }
+
+    /// moves the reserves along the curve after a sell
+    pub fn apply_sell(&mut self, tokens: u64, lamports: u64) -> Result<()> {
+        self.accrue_price()?;
+        self.virtual_token_reserves = self
+            .virtual_token_reserves
+            .checked_add(tokens)
+            .ok_or(MathOverflow)?;
+        self.virtual_lamport_reserves = self
+            .virtual_lamport_reserves
+            .checked_sub(lamports)
+            .ok_or(InsufficientReserves)?;
+        self.real_lamport_reserves = self
+            .real_lamport_reserves
+            .checked_sub(lamports)
+            .ok_or(InsufficientReserves)?;
+
+        Ok(())
+    }
+}
+
+/// largest `x` in `low..=high` for which `fits(x)` holds, given that `fits(low)` holds and `fits`
+/// stops holding past some point
+fn last_fitting(
+    mut low: u64,
+    mut high: u64,
+    mut fits: impl FnMut(u64) -> Result<bool>,
+) -> Result<u64> {
+    while low < high {
+        let mid = high - (high - low) / 2;
+        if fits(mid)? {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+
+    Ok(low)
+}
+
+/// smallest `x` in `low..=high` for which `covers(x)` holds, given that `covers(high)` holds and
+/// `covers` keeps holding past some point
+fn first_covering(
+    mut low: u64,
+    mut high: u64,
+    mut covers: impl FnMut(u64) -> Result<bool>,
+) -> Result<u64> {
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if covers(mid)? {
+            high = mid;
+        } else {
+            low = mid + 1;
+        }
+    }
+
+    Ok(low)
+}
+
+/// Price curve a launch trades on, every kind starts at `virtual_lamport_reserves / token_supply`
+/// lamports per token
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, PartialOrd, Debug)]
+pub enum CurveKind {
+    /// constant product of the virtual lamport and token reserves
+    ConstantProduct,
+    /// price rises linearly to `final_price_multiple` times the starting price once the supply
+    /// is sold out
+    Linear { final_price_multiple: u16 },
+    /// price doubles `doublings` times over the supply
+    Exponential { doublings: u8 },
+}
+
+impl CurveKind {
+    pub const MAX_LEN: usize = 1 + 2;
+    pub const MAX_DOUBLINGS: u8 = 16;
+
+    pub fn validate(&self) -> Result<()> {
+        match *self {
+            Self::ConstantProduct => {}
+            Self::Linear {
+                final_price_multiple,
+            } => {
+                if final_price_multiple == 0 {
+                    msg!("final_price_multiple must be positive");
+                    return Err(ValueTooSmall.into());
+                }
+            }
+            Self::Exponential { doublings } => {
+                if doublings == 0 || doublings > Self::MAX_DOUBLINGS {
+                    msg!(
+                        "doublings must be between 1 and {}, got {doublings}",
+                        Self::MAX_DOUBLINGS
+                    );
+                    return Err(ValueInvalid.into());
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// lamports paid for the first `sold` tokens of `supply`, rounded down
+    pub fn lamports_for_sold(&self, sold: u64, supply: u64, initial_lamports: u64) -> Result<u64> {
+        let (sold, supply, initial_lamports) =
+            (sold as u128, supply as u128, initial_lamports as u128);
+
+        let lamports = match *self {
+            // V * s / (S - s)
+            Self::ConstantProduct => {
+                if sold >= supply {
+                    return Err(InsufficientReserves.into());
+                }
+                initial_lamports * sold / (supply - sold)
+            }
+            // V * s / S + V * (M - 1) * s^2 / 2S^2
+            Self::Linear {
+                final_price_multiple,
+            } => {
+                let sold_squared = sold * sold / supply;
+                let growth = initial_lamports
+                    .checked_mul(final_price_multiple as u128 - 1)
+                    .and_then(|growth| growth.checked_mul(sold_squared))
+                    .ok_or(MathOverflow)?
+                    / (2 * supply);
+                initial_lamports * sold / supply + growth
+            }
+            // V * (2^(d * s / S) - 1) / (d * ln 2)
+            Self::Exponential { doublings } => {
+                let exponent = ((doublings as u128 * sold) << 32) / supply;
+                let growth = exp2_q63(exponent)? - Q63_ONE;
+                initial_lamports.checked_mul(growth).ok_or(MathOverflow)?
+                    / (doublings as u128 * LN_2_Q63)
+            }
+        };
+
+        u64::try_from(lamports).map_err(|_| MathOverflow.into())
+    }
+
+    /// derivative of `lamports_for_sold` in Q64 lamports per token base unit
+    pub fn spot_price_q64(&self, sold: u64, supply: u64, initial_lamports: u64) -> Result<u128> {
+        let (sold, supply) = (sold as u128, supply as u128);
+        // starting price V / S
+        let base_price = ((initial_lamports as u128) << 64) / supply;
+
+        let price = match *self {
+            // V * S / (S - s)^2
+            Self::ConstantProduct => {
+                if sold >= supply {
+                    return Err(InsufficientReserves.into());
+                }
+                let remaining = supply - sold;
+                (((initial_lamports as u128) << 64) / remaining)
+                    .checked_mul(supply)
+                    .ok_or(MathOverflow)?
+                    / remaining
+            }
+            // V / S * (1 + (M - 1) * s / S)
+            Self::Linear {
+                final_price_multiple,
+            } => {
+                let sold_fraction_q32 = (sold << 32) / supply;
+                let growth = base_price
+                    .checked_mul(final_price_multiple as u128 - 1)
+                    .and_then(|growth| growth.checked_mul(sold_fraction_q32))
+                    .ok_or(MathOverflow)?;
+                base_price + (growth >> 32)
+            }
+            // V / S * 2^(d * s / S)
+            Self::Exponential { doublings } => {
+                let exponent = ((doublings as u128 * sold) << 32) / supply;
+                base_price
+                    .checked_mul(exp2_q63(exponent)? >> 32)
+                    .ok_or(MathOverflow)?
+                    >> 31
+            }
+        };
+
+        Ok(price)
+    }
+}
+
+/// Metadata update authority handling at `migrate`, until then the `TokenLaunch` PDA holds it so
+/// the creator can call `update_launch_metadata`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MetadataMigrationPolicy {
+    /// drop the update authority so the metadata can no longer change
+    Freeze,
+    /// transfer the update authority to the launch creator
+    HandToCreator,
+}
+
+/// Token program a launch mints under
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TokenStandard {
+    /// spl-token mint described by a Metaplex metadata account
+    SplToken,
+    /// Token-2022 mint carrying its own metadata through the metadata-pointer and token-metadata
+    /// extensions
+    Token2022,
+}
+
+impl TokenStandard {
+    pub fn program_id(&self) -> Pubkey {
+        match self {
+            Self::SplToken => anchor_spl::token::ID,
+            Self::Token2022 => anchor_spl::token_2022::ID,
+        }
+    }
+}
+
+/// Cap on the tokens a wallet may hold after a curve buy, for the first `duration_seconds` of
+/// `OpenSale`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct MaxWalletLimit {
+    /// share of `initial_token_max_supply`
+    pub bps: u16,
+    pub duration_seconds: i64,
+}
+
+impl MaxWalletLimit {
+    pub const DATA_LEN: usize = 2 + 8;
+
+    pub fn validate(&self, config: &Config) -> Result<()> {
+        if self.bps == 0 || self.bps as u64 > BPS_DENOMINATOR {
+            msg!(
+                "max wallet bps must be between 1 and {BPS_DENOMINATOR}, got {}",
+                self.bps
+            );
+            return Err(ValueInvalid.into());
+        }
+        config.max_wallet_bps_config.validate(&self.bps)?;
+        config
+            .max_wallet_duration_config
+            .validate(&self.duration_seconds)
+    }
+}
+
+/// Token-2022 transfer-fee extension set up on the mint at launch.
+///
+/// The fee is withheld by the token program on every transfer, including curve trades, and can be
+/// collected by the creator with `harvest_transfer_fees`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct LaunchTransferFee {
+    pub basis_points: u16,
+    pub maximum_fee: u64,
+}
+
+impl LaunchTransferFee {
+    pub const DATA_LEN: usize = 2 + 8;
+}
+
+/// Side of a trade the user fixes, and the slippage bound on the other side
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SwapAmount {
+    ExactIn {
+        amount_in: u64,
+        minimum_amount_out: u64,
+    },
+    ExactOut {
+        max_amount_in: u64,
+        amount_out: u64,
+    },
+}
+
+/// Point after which a curve trade is rejected, so a delayed transaction cannot fill at a stale price
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SwapDeadline {
+    /// last unix timestamp the trade may execute at
+    UnixTimestamp(i64),
+    /// last slot the trade may execute in
+    Slot(u64),
+}
+
+impl SwapDeadline {
+    pub fn check(&self) -> Result<()> {
+        let clock = Clock::get()?;
+        let expired = match *self {
+            Self::UnixTimestamp(deadline) => clock.unix_timestamp > deadline,
+            Self::Slot(deadline) => clock.slot > deadline,
+        };
+        if expired {
+            msg!(&format!("trade deadline {self:?} has passed"));
+            return Err(DeadlineExceeded.into());
+        }
+
+        Ok(())
+    }
+}
+
+/// What happens to a curve sell within `Config.sandwich_window_slots` of the same wallet's buy
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SandwichPolicy {
+    Off,
+    /// the sell fails
+    Reject,
+    /// the sell pays `fee_bps` of its lamports to the launch creator on top of the platform fee
+    Penalty {
+        fee_bps: u16,
+    },
+}
+
+/// Direction of a trade against the bonding curve
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SwapDirection {
+    /// lamports in, tokens out
+    Buy,
+    /// tokens in, lamports out
+    Sell,
+}
+
+/// How the presale token cap is shared out between snipers at `close_presale`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub enum AllocationMode {
+    /// fill snipers one by one in `(bid_amount, buy_lamports)` order until the cap is reached
+    StrictPriority,
+    /// every sniper is filled in proportion to their `buy_lamports` at one uniform price
+    ProRata,
+    /// every sniper is filled up to `guaranteed_lamports` first, the rest is shared pro-rata
+    Hybrid { guaranteed_lamports: u64 },
+}
+
+impl AllocationMode {
+    pub const MAX_LEN: usize = 1 + 8;
+}
+
+/// Vesting applied to presale allocations.
+///
+/// `tge_bps` of each allocation is sent straight to the sniper by `process_snipes`, the rest is
+/// locked in a `VestingSchedule` and released linearly over `duration_seconds` once
+/// `cliff_seconds` have passed.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct VestingConfig {
+    pub cliff_seconds: i64,
+    pub duration_seconds: i64,
+    pub tge_bps: u16,
+}
+
+impl VestingConfig {
+    pub const DATA_LEN: usize = 8 + 8 + 2;
+
+    pub fn validate(&self) -> Result<()> {
+        if self.cliff_seconds < 0 || self.duration_seconds < 0 {
+            msg!(&format!(
+                "vesting cliff and duration cannot be negative, got {self:?}"
+            ));
+            return Err(ValueTooSmall.into());
+        }
+        if self.tge_bps as u64 > BPS_DENOMINATOR {
+            msg!("tge_bps {} exceeds {BPS_DENOMINATOR}", self.tge_bps);
+            return Err(ValueTooLarge.into());
+        }
+
+        Ok(())
+    }
+
+    /// part of `token_amount` released at presale settlement
+    pub fn tge_amount(&self, token_amount: u64) -> u64 {
+        ((token_amount as u128) * (self.tge_bps as u128) / (BPS_DENOMINATOR as u128)) as u64
+    }
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
@@ -76,7 +837,10 @@ pub enum LaunchPhase {
     ProcessingPresale,
     OpenSale,
     Completed,
-} 
+    Cancelled,
+    /// liquidity handed to the AMM by `migrate`, the launch accounts can be closed
+    Migrated,
+}
 
 impl LaunchPhase {
     pub fn assert_eq(&self, phase: &Self) -> Result<()> {
@@ -97,6 +861,15 @@ pub struct SnipeQueue {
 
 impl SnipeQueue {
     pub const MIN_ACCOUNT_LEN: usize = 8 + 32 + 4;
+
+    /// rent each sniper pays for the space their `SnipeConfig` adds to the queue
+    pub fn snipe_rent() -> Result<u64> {
+        let rent = Rent::get()?;
+        Ok(
+            rent.minimum_balance(Self::MIN_ACCOUNT_LEN + SnipeConfig::DATA_LEN)
+                - rent.minimum_balance(Self::MIN_ACCOUNT_LEN),
+        )
+    }
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, Eq, Debug)]
@@ -105,11 +878,14 @@ pub struct SnipeConfig {
     pub bid_amount: u64,
     pub buy_lamports: u64,
     pub token_amount: u64,
+    pub refund_lamports: u64,
     pub processed: bool,
+    /// `Referrer` account credited with part of the platform share of the bid
+    pub referrer: Option<Pubkey>,
 }
 
 impl SnipeConfig {
-    pub const DATA_LEN: usize = 32 + 8 + 8 + 8 + 1;
+    pub const DATA_LEN: usize = 32 + 8 + 8 + 8 + 8 + 1 + 1 + 32;
 }
 
 impl Ord for SnipeConfig {
@@ -129,3 +905,611 @@ impl PartialEq for SnipeConfig {
         (self.bid_amount, self.buy_lamports) == (other.bid_amount, other.buy_lamports)
     }
 }
+
+#[account]
+pub struct VestingSchedule {
+    pub token: Pubkey,
+    pub wallet: Pubkey,
+    pub total_amount: u64,
+    pub claimed_amount: u64,
+    pub start_timestamp: i64,
+    pub cliff_seconds: i64,
+    pub duration_seconds: i64,
+}
+
+impl VestingSchedule {
+    pub const ACCOUNT_LEN: usize = 8 + 32 + 32 + 8 + 8 + 8 + 8 + 8;
+
+    /// total amount released by `now`, claimed or not
+    pub fn unlocked_amount(&self, now: i64) -> u64 {
+        let vesting_start = self.start_timestamp.saturating_add(self.cliff_seconds);
+        if now < vesting_start {
+            return 0;
+        }
+
+        let elapsed = now - vesting_start;
+        if elapsed >= self.duration_seconds {
+            return self.total_amount;
+        }
+
+        ((self.total_amount as u128) * (elapsed as u128) / (self.duration_seconds as u128)) as u64
+    }
+}
+
+/// Wallet earning part of the platform fee on the trades and snipes it refers
+#[account]
+pub struct Referrer {
+    pub wallet: Pubkey,
+    /// referral fees held by this account until claimed
+    pub claimable_lamports: u64,
+    pub claimed_lamports: u64,
+    /// lamports bought, sold and sniped through this referrer
+    pub referred_volume: u64,
+}
+
+impl Referrer {
+    pub const ACCOUNT_LEN: usize = 8 + 32 + 8 + 8 + 8;
+
+    /// records a referred trade of `volume` lamports that paid `referral_fee_lamports`
+    pub fn record(&mut self, volume: u64, referral_fee_lamports: u64) -> Result<()> {
+        self.claimable_lamports = self
+            .claimable_lamports
+            .checked_add(referral_fee_lamports)
+            .ok_or(MathOverflow)?;
+        self.referred_volume = self.referred_volume.saturating_add(volume);
+
+        Ok(())
+    }
+}
+
+/// Counts every launch, giving each `TokenLaunch` its `launch_id`
+#[account]
+pub struct LaunchRegistry {
+    pub launch_count: u64,
+}
+
+impl LaunchRegistry {
+    pub const ACCOUNT_LEN: usize = 8 + 8;
+}
+
+/// Launch counters of a creator, their launches are listed in `CreatorIndexPage`s
+#[account]
+pub struct CreatorIndex {
+    pub creator: Pubkey,
+    pub launch_count: u32,
+    /// launches of this creator still in `Presale`
+    pub active_presales: u16,
+}
+
+impl CreatorIndex {
+    pub const ACCOUNT_LEN: usize = 8 + 32 + 4 + 2;
+
+    /// page the next launch of this creator is listed on
+    pub fn current_page(&self) -> u32 {
+        self.launch_count / CreatorIndexPage::CAPACITY as u32
+    }
+
+    /// called when a launch of this creator leaves `Presale`
+    pub fn end_presale(&mut self) {
+        self.active_presales = self.active_presales.saturating_sub(1);
+    }
+}
+
+/// Page `page` of a creator's launch mints, in launch order
+#[account]
+pub struct CreatorIndexPage {
+    pub creator: Pubkey,
+    pub page: u32,
+    pub tokens: Vec<Pubkey>,
+}
+
+impl CreatorIndexPage {
+    pub const CAPACITY: usize = 32;
+    pub const ACCOUNT_LEN: usize = 8 + 32 + 4 + 4 + 32 * Self::CAPACITY;
+}
+
+/// Resting order against the bonding curve of a lamport launch, filled by `fill_curve_orders` once
+/// the curve reaches its limit.
+///
+/// Buy orders escrow `amount_in` lamports with the platform fee in this account, sell orders escrow
+/// their tokens in the associated token account of this account. Both escrow the cranker tip here.
+#[account]
+pub struct CurveOrder {
+    pub token: Pubkey,
+    pub owner: Pubkey,
+    pub order_id: u64,
+    pub direction: SwapDirection,
+    /// lamports a buy spends on the curve, tokens a sell received in escrow
+    pub amount_in: u64,
+    /// tokens a buy of `amount_in` must return after transfer fees, pro rata when the buy is cut
+    /// short at graduation, or lamports a sell must return after the platform fee
+    pub limit_amount_out: u64,
+    /// platform fee when the order was placed
+    pub fee_bps: u16,
+    pub tip_lamports: u64,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+impl CurveOrder {
+    pub const ACCOUNT_LEN: usize = 8 + 32 + 32 + 8 + 1 + 8 + 8 + 2 + 8 + 8 + 1;
+}
+
+/// Snapshots of a launch's `price_cumulative`, at most one per `MIN_SPACING_SECONDS`, kept in a
+/// ring so `get_twap` can average the price over a recent window
+#[account]
+pub struct PriceObservations {
+    pub token: Pubkey,
+    /// position of the newest observation
+    pub newest: u16,
+    pub observations: Vec<PriceObservation>,
+}
+
+impl PriceObservations {
+    pub const CAPACITY: usize = 32;
+    /// spreads the ring over at least `CAPACITY * MIN_SPACING_SECONDS` of trading
+    pub const MIN_SPACING_SECONDS: i64 = 60;
+    pub const ACCOUNT_LEN: usize = 8 + 32 + 2 + 4 + PriceObservation::DATA_LEN * Self::CAPACITY;
+
+    /// records the launch's `price_cumulative`, overwriting the oldest observation once full.
+    /// Skipped while the newest observation is younger than `MIN_SPACING_SECONDS`
+    pub fn record(&mut self, token_launch: &TokenLaunch) {
+        let observation = PriceObservation {
+            timestamp: token_launch.price_updated_at,
+            price_cumulative: token_launch.price_cumulative,
+        };
+        match self.observations.get(self.newest as usize) {
+            Some(newest)
+                if observation.timestamp - newest.timestamp < Self::MIN_SPACING_SECONDS => {}
+            _ if self.observations.len() < Self::CAPACITY => {
+                self.newest = self.observations.len() as u16;
+                self.observations.push(observation);
+            }
+            _ => {
+                self.newest = (self.newest + 1) % Self::CAPACITY as u16;
+                self.observations[self.newest as usize] = observation;
+            }
+        }
+    }
+
+    /// time-weighted average price over the `window_seconds` before `now`, in Q64 lamports per
+    /// token base unit
+    pub fn twap_q64(
+        &self,
+        token_launch: &TokenLaunch,
+        now: i64,
+        window_seconds: u32,
+    ) -> Result<u128> {
+        if window_seconds == 0 {
+            msg!("window_seconds must be positive");
+            return Err(ValueTooSmall.into());
+        }
+
+        // the observations in time order, then the launch as of now
+        let mut points = self.observations.clone();
+        points.sort_by_key(|observation| observation.timestamp);
+        points.push(PriceObservation {
+            timestamp: now,
+            price_cumulative: token_launch.price_cumulative_at(now)?,
+        });
+
+        // interpolate linearly within the points holding `start`, exact when no trade fell between
+        // them and an approximation when `record` skipped trades closer than `MIN_SPACING_SECONDS`
+        let start = now - window_seconds as i64;
+        let Some(index) = points
+            .iter()
+            .rposition(|observation| observation.timestamp <= start)
+        else {
+            msg!(
+                "window of {window_seconds} seconds is older than the first observation at {}",
+                points[0].timestamp
+            );
+            return Err(ValueTooLarge.into());
+        };
+        let (before, after) = (&points[index], &points[index + 1]);
+        let start_cumulative = before.price_cumulative.wrapping_add(
+            after.price_cumulative.wrapping_sub(before.price_cumulative)
+                / (after.timestamp - before.timestamp) as u128
+                * (start - before.timestamp) as u128,
+        );
+
+        Ok(points[points.len() - 1]
+            .price_cumulative
+            .wrapping_sub(start_cumulative)
+            / window_seconds as u128)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PriceObservation {
+    pub timestamp: i64,
+    pub price_cumulative: u128,
+}
+
+impl PriceObservation {
+    pub const DATA_LEN: usize = 8 + 16;
+}
+
+/// Last curve buy of a wallet on a launch, used to catch sandwiches.
+/// Doubles as the wallet's receipt for `LaunchStats.unique_traders`
+#[account]
+pub struct TraderState {
+    pub token: Pubkey,
+    pub wallet: Pubkey,
+    pub last_buy_slot: Option<u64>,
+}
+
+impl TraderState {
+    pub const ACCOUNT_LEN: usize = 8 + 32 + 32 + 1 + 8;
+
+    /// penalty a sell by this wallet pays in `slot`, fails if `Config.sandwich_policy` rejects it
+    pub fn sell_penalty_bps(&self, config: &Config, slot: u64) -> Result<u16> {
+        let Some(last_buy_slot) = self.last_buy_slot else {
+            return Ok(0);
+        };
+        if slot > last_buy_slot.saturating_add(config.sandwich_window_slots) {
+            return Ok(0);
+        }
+
+        match config.sandwich_policy {
+            SandwichPolicy::Off => Ok(0),
+            SandwichPolicy::Reject => {
+                msg!(
+                    "{} bought in slot {last_buy_slot}, cannot sell until slot {}",
+                    self.wallet,
+                    last_buy_slot.saturating_add(config.sandwich_window_slots) + 1
+                );
+                Err(SellTooSoonAfterBuy.into())
+            }
+            SandwichPolicy::Penalty { fee_bps } => Ok(fee_bps),
+        }
+    }
+}
+
+/// Running trading totals of a launch, so dashboards can read volume without an indexer
+#[account]
+pub struct LaunchStats {
+    pub token: Pubkey,
+    /// lamports, or quote tokens of SPL-quoted launches, spent on the curve before fees
+    pub buy_lamports: u64,
+    /// lamports, or quote tokens, paid out by the curve before fees
+    pub sell_lamports: u64,
+    pub trade_count: u64,
+    /// wallets that opened a `TraderState` for the launch
+    pub unique_traders: u64,
+    /// highest spot price after a trade, in Q64 lamports per token base unit
+    pub ath_price_q64: u128,
+    pub last_trade_at: i64,
+}
+
+impl LaunchStats {
+    pub const ACCOUNT_LEN: usize = 8 + 32 + 8 + 8 + 8 + 8 + 16 + 8;
+
+    /// adds a curve trade that left the curve at `token_launch`
+    pub fn record_trade(
+        &mut self,
+        token_launch: &TokenLaunch,
+        direction: SwapDirection,
+        lamport_amount: u64,
+        now: i64,
+    ) -> Result<()> {
+        match direction {
+            SwapDirection::Buy => {
+                self.buy_lamports = self.buy_lamports.saturating_add(lamport_amount)
+            }
+            SwapDirection::Sell => {
+                self.sell_lamports = self.sell_lamports.saturating_add(lamport_amount)
+            }
+        }
+        self.trade_count += 1;
+        self.ath_price_q64 = self.ath_price_q64.max(token_launch.spot_price_q64()?);
+        self.last_trade_at = now;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    const SUPPLY: u64 = 1_000_000_000_000_000;
+    const VIRTUAL_LAMPORTS: u64 = 30_000_000_000;
+
+    const CURVE_KINDS: [CurveKind; 4] = [
+        CurveKind::ConstantProduct,
+        CurveKind::Linear {
+            final_price_multiple: 10,
+        },
+        CurveKind::Exponential { doublings: 4 },
+        CurveKind::Exponential {
+            doublings: CurveKind::MAX_DOUBLINGS,
+        },
+    ];
+
+    /// points along the supply, up to 99.9% sold
+    fn sold_points() -> impl Iterator<Item = u64> {
+        (0..=999).map(|i| SUPPLY / 1000 * i)
+    }
+
+    /// `lamports_for_sold` and `spot_price_q64` in floating point, the price in lamports per
+    /// token base unit
+    fn reference(curve_kind: CurveKind, sold: u64) -> (f64, f64) {
+        let (v, s, sold) = (VIRTUAL_LAMPORTS as f64, SUPPLY as f64, sold as f64);
+        match curve_kind {
+            CurveKind::ConstantProduct => {
+                (v * sold / (s - sold), v * s / ((s - sold) * (s - sold)))
+            }
+            CurveKind::Linear {
+                final_price_multiple,
+            } => {
+                let growth = final_price_multiple as f64 - 1.0;
+                (
+                    v * sold / s + v * growth * sold * sold / (2.0 * s * s),
+                    v / s * (1.0 + growth * sold / s),
+                )
+            }
+            CurveKind::Exponential { doublings } => {
+                let d = doublings as f64;
+                let multiple = (d * sold / s).exp2();
+                (
+                    v * (multiple - 1.0) / (d * std::f64::consts::LN_2),
+                    v / s * multiple,
+                )
+            }
+        }
+    }
+
+    /// a launch with nothing sold, on a 1e15 token supply and 30 SOL of virtual reserves
+    pub(crate) fn launch(curve_kind: CurveKind) -> TokenLaunch {
+        TokenLaunch {
+            launch_id: 0,
+            token: Pubkey::new_unique(),
+            token_standard: TokenStandard::SplToken,
+            transfer_fee: None,
+            curve_kind,
+            quote_mint: NATIVE_MINT,
+            creator: Pubkey::new_unique(),
+            phase: LaunchPhase::OpenSale,
+            created_at: 0,
+            presale_start: 0,
+            presale_end: 0,
+            virtual_lamport_reserves: VIRTUAL_LAMPORTS,
+            virtual_token_reserves: SUPPLY,
+            initial_token_max_supply: SUPPLY,
+            initial_virtual_lamport_reserves: VIRTUAL_LAMPORTS,
+            real_lamport_reserves: 0,
+            graduation_lamports: u64::MAX,
+            allocation_mode: AllocationMode::StrictPriority,
+            presale_token_cap: 0,
+            vesting: None,
+            dev_buy_lamports: 0,
+            dev_buy_token_amount: 0,
+            price_cumulative: 0,
+            price_updated_at: 0,
+            max_wallet: None,
+            sale_opened_at: 0,
+            presale_settled_at: 0,
+        }
+    }
+
+    /// moves the reserves like `apply_buy`, without the clock
+    fn buy(launch: &mut TokenLaunch, lamports: u64) -> u64 {
+        let tokens = launch.quote_buy(lamports).unwrap();
+        launch.virtual_lamport_reserves += lamports;
+        launch.virtual_token_reserves -= tokens;
+        launch.real_lamport_reserves += lamports;
+        tokens
+    }
+
+    /// moves the reserves like `apply_sell`, without the clock
+    fn sell(launch: &mut TokenLaunch, tokens: u64) -> u64 {
+        let lamports = launch.quote_sell(tokens).unwrap();
+        launch.virtual_token_reserves += tokens;
+        launch.virtual_lamport_reserves -= lamports;
+        launch.real_lamport_reserves -= lamports;
+        lamports
+    }
+
+    #[test]
+    fn curves_start_at_the_same_price() {
+        let base_price = ((VIRTUAL_LAMPORTS as u128) << 64) / SUPPLY as u128;
+        for curve_kind in CURVE_KINDS {
+            assert_eq!(
+                curve_kind
+                    .lamports_for_sold(0, SUPPLY, VIRTUAL_LAMPORTS)
+                    .unwrap(),
+                0
+            );
+            assert_eq!(
+                curve_kind
+                    .spot_price_q64(0, SUPPLY, VIRTUAL_LAMPORTS)
+                    .unwrap(),
+                base_price,
+                "{curve_kind:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn lamports_for_sold_matches_float_reference() {
+        for curve_kind in CURVE_KINDS {
+            for sold in sold_points() {
+                let lamports = curve_kind
+                    .lamports_for_sold(sold, SUPPLY, VIRTUAL_LAMPORTS)
+                    .unwrap() as f64;
+                let (expected, _) = reference(curve_kind, sold);
+                // exponents are rounded to Q32, so allow a few lamports and 1e-7 relative error
+                assert!(
+                    (lamports - expected).abs() <= 2.0 + expected * 1e-7,
+                    "{curve_kind:?} sold {sold}: {lamports} vs {expected}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn spot_price_matches_float_reference() {
+        for curve_kind in CURVE_KINDS {
+            for sold in sold_points() {
+                let price = curve_kind
+                    .spot_price_q64(sold, SUPPLY, VIRTUAL_LAMPORTS)
+                    .unwrap() as f64
+                    / 2.0_f64.powi(64);
+                let (_, expected) = reference(curve_kind, sold);
+                assert!(
+                    (price - expected).abs() <= expected * 1e-8,
+                    "{curve_kind:?} sold {sold}: {price} vs {expected}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn curves_are_monotonic() {
+        for curve_kind in CURVE_KINDS {
+            let mut previous = (0, 0);
+            for sold in sold_points().flat_map(|sold| sold..sold + 3) {
+                let point = (
+                    curve_kind
+                        .lamports_for_sold(sold, SUPPLY, VIRTUAL_LAMPORTS)
+                        .unwrap(),
+                    curve_kind
+                        .spot_price_q64(sold, SUPPLY, VIRTUAL_LAMPORTS)
+                        .unwrap(),
+                );
+                assert!(
+                    point.0 >= previous.0 && point.1 >= previous.1,
+                    "{curve_kind:?} decreases at sold {sold}"
+                );
+                previous = point;
+            }
+        }
+    }
+
+    #[test]
+    fn quote_buy_returns_most_tokens_within_budget() {
+        for curve_kind in CURVE_KINDS {
+            let mut launch = launch(curve_kind);
+            for lamports in [1, 1_000, 1_000_000_000, 10_000_000_000] {
+                let tokens = launch.quote_buy(lamports).unwrap();
+                assert!(launch.quote_lamports_for_tokens(tokens).unwrap() <= lamports);
+                assert!(
+                    launch.quote_lamports_for_tokens(tokens + 1).unwrap() > lamports,
+                    "{curve_kind:?} leaves tokens unbought for {lamports} lamports"
+                );
+                buy(&mut launch, lamports);
+            }
+        }
+    }
+
+    #[test]
+    fn quote_tokens_for_lamports_returns_fewest_tokens_covering_lamports() {
+        for curve_kind in CURVE_KINDS {
+            let mut launch = launch(curve_kind);
+            buy(&mut launch, 20_000_000_000);
+            for lamports in [1, 1_000, 1_000_000_000, 10_000_000_000] {
+                let tokens = launch.quote_tokens_for_lamports(lamports).unwrap();
+                assert!(launch.quote_sell(tokens).unwrap() >= lamports);
+                assert!(
+                    launch.quote_sell(tokens - 1).unwrap() < lamports,
+                    "{curve_kind:?} sells too many tokens for {lamports} lamports"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn buy_then_sell_pays_out_at_most_the_buy() {
+        for curve_kind in CURVE_KINDS {
+            let mut launch = launch(curve_kind);
+            buy(&mut launch, 5_000_000_000);
+            for lamports in [1, 7, 999, 123_456_789, 5_000_000_000] {
+                let tokens = buy(&mut launch, lamports);
+                let refund = sell(&mut launch, tokens);
+                assert!(
+                    refund <= lamports,
+                    "{curve_kind:?} pays {refund} for a {lamports} lamport buy"
+                );
+            }
+
+            // many small buys sold at once
+            let tokens: u64 = (0..100).map(|_| buy(&mut launch, 1_234_567)).sum();
+            assert!(sell(&mut launch, tokens) <= 100 * 1_234_567);
+        }
+    }
+
+    #[test]
+    fn searches_probe_at_most_log2_of_their_range() {
+        // a launch's whole supply fits in 50 bits, so a quote evaluates the curve at most 50 times
+        for target in [0, 1, SUPPLY / 3, SUPPLY - 2, SUPPLY - 1] {
+            let mut probes = 0;
+            let tokens = last_fitting(0, SUPPLY - 1, |tokens| {
+                probes += 1;
+                Ok(tokens <= target)
+            })
+            .unwrap();
+            assert_eq!(tokens, target);
+            assert!(probes <= 50, "{probes} probes for {target}");
+
+            let mut probes = 0;
+            let tokens = first_covering(0, SUPPLY - 1, |tokens| {
+                probes += 1;
+                Ok(tokens >= target)
+            })
+            .unwrap();
+            assert_eq!(tokens, target);
+            assert!(probes <= 50, "{probes} probes for {target}");
+        }
+    }
+
+    #[test]
+    fn twap_interpolates_across_skipped_observations() {
+        let mut launch = launch(CurveKind::ConstantProduct);
+        let mut observations = PriceObservations {
+            token: launch.token,
+            newest: 0,
+            observations: vec![],
+        };
+        observations.record(&launch);
+
+        // `price_cumulative` as of every second, the way `accrue_price` keeps it
+        let mut cumulative = vec![0u128];
+        let mut prices = vec![launch.spot_price_q64().unwrap()];
+        for now in 1..=100 {
+            cumulative.push(launch.price_cumulative_at(now).unwrap());
+            if now % 10 == 0 && now <= 70 {
+                launch.price_cumulative = launch.price_cumulative_at(now).unwrap();
+                launch.price_updated_at = now;
+                buy(&mut launch, 1_000_000_000);
+                prices.push(launch.spot_price_q64().unwrap());
+                observations.record(&launch);
+            }
+        }
+
+        // only the trade at 60 is stored, the ones at 10 to 50 and 70 are skipped
+        assert_eq!(
+            observations
+                .observations
+                .iter()
+                .map(|observation| observation.timestamp)
+                .collect::<Vec<_>>(),
+            [0, 60]
+        );
+
+        let exact =
+            |window: u32| (cumulative[100] - cumulative[100 - window as usize]) / window as u128;
+        let twap = |window: u32| observations.twap_q64(&launch, 100, window).unwrap();
+
+        // windows starting on a stored observation are exact
+        assert_eq!(twap(100), exact(100));
+        assert_eq!(twap(40), exact(40));
+
+        // inside the skipped stretch the start is interpolated, off by at most the price range
+        let price_range = prices.iter().max().unwrap() - prices.iter().min().unwrap();
+        for window in [20, 50, 70, 95] {
+            assert_ne!(twap(window), exact(window));
+            assert!(twap(window).abs_diff(exact(window)) <= price_range);
+        }
+    }
+}
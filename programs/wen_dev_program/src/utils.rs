@@ -0,0 +1,620 @@
+use crate::{constants::BPS_DENOMINATOR, errors::*};
+use anchor_lang::{prelude::*, solana_program::program::invoke, system_program};
+use anchor_spl::metadata::mpl_token_metadata::{
+    MAX_NAME_LENGTH, MAX_SYMBOL_LENGTH, MAX_URI_LENGTH,
+};
+use anchor_spl::token_2022::spl_token_2022::{
+    extension::{
+        transfer_fee::{instruction::harvest_withheld_tokens_to_mint, TransferFeeConfig},
+        BaseStateWithExtensions, StateWithExtensions,
+    },
+    state::Mint,
+};
+use anchor_spl::token_interface::{self, TokenInterface};
+
+/// Size of a Raydium `AmmInfo` account
+const AMM_INFO_LEN: usize = 752;
+/// Offsets of `AmmInfo.nonce` and `AmmInfo.coin_vault`, the first of its consecutive keys
+const AMM_INFO_NONCE_OFFSET: usize = 8;
+const AMM_INFO_KEYS_OFFSET: usize = 336;
+
+/// Reads an SPL Token account the AMM proxy takes unchecked
+pub fn unpack_token_account(
+    token_account: &AccountInfo,
+) -> Result<anchor_spl::token::TokenAccount> {
+    if *token_account.owner != anchor_spl::token::ID {
+        msg!("{} is not an SPL Token account", token_account.key());
+        return Err(IncorrectTokenAccount.into());
+    }
+
+    anchor_spl::token::TokenAccount::try_deserialize(&mut &token_account.data.borrow()[..])
+}
+
+/// Nonce and keys of a Raydium pool, as recorded in its `AmmInfo`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AmmKeys {
+    pub nonce: u64,
+    pub coin_vault: Pubkey,
+    pub pc_vault: Pubkey,
+    pub coin_vault_mint: Pubkey,
+    pub pc_vault_mint: Pubkey,
+    pub lp_mint: Pubkey,
+    pub open_orders: Pubkey,
+    pub market: Pubkey,
+    pub market_program: Pubkey,
+    pub target_orders: Pubkey,
+}
+
+/// Reads the nonce and keys of a Raydium pool from its `AmmInfo`.
+///
+/// The AMM program packs `AmmInfo` with 8-byte aligned `u128`s, so the fields are read at their
+/// on-chain offsets rather than by casting the account data.
+pub fn load_amm_keys(amm: &AccountInfo) -> Result<AmmKeys> {
+    if *amm.owner != amm_anchor::ID {
+        msg!("{} is not owned by the AMM program", amm.key());
+        return Err(ErrorCode::AccountOwnedByWrongProgram.into());
+    }
+    let data = amm.try_borrow_data()?;
+    if data.len() < AMM_INFO_LEN {
+        return Err(ErrorCode::AccountDidNotDeserialize.into());
+    }
+
+    let key = |index: usize| {
+        let offset = AMM_INFO_KEYS_OFFSET + 32 * index;
+        Pubkey::try_from(&data[offset..offset + 32]).map_err(|_| SerializationFailed)
+    };
+    Ok(AmmKeys {
+        nonce: u64::from_le_bytes(
+            data[AMM_INFO_NONCE_OFFSET..AMM_INFO_NONCE_OFFSET + 8]
+                .try_into()
+                .map_err(|_| SerializationFailed)?,
+        ),
+        coin_vault: key(0)?,
+        pc_vault: key(1)?,
+        coin_vault_mint: key(2)?,
+        pc_vault_mint: key(3)?,
+        lp_mint: key(4)?,
+        open_orders: key(5)?,
+        market: key(6)?,
+        market_program: key(7)?,
+        target_orders: key(8)?,
+    })
+}
+
+/// Authority PDA of a Raydium pool
+pub fn amm_authority(amm_keys: &AmmKeys) -> Result<Pubkey> {
+    Pubkey::create_program_address(
+        &[b"amm authority", &[amm_keys.nonce as u8]],
+        &amm_anchor::ID,
+    )
+    .map_err(|_| IncorrectAmmAccount.into())
+}
+
+/// Fails unless every `(key, expected)` pair of accounts passed for the pool `amm` matches
+pub fn assert_amm_accounts(amm: &AccountInfo, accounts: &[(Pubkey, Pubkey)]) -> Result<()> {
+    for (key, expected) in accounts {
+        if key != expected {
+            msg!("expected {expected} for pool {}, got {key}", amm.key());
+            return Err(IncorrectAmmAccount.into());
+        }
+    }
+
+    Ok(())
+}
+
+/// Creates the PDA `account` with `space` bytes owned by this program, signed by `signer_seeds`.
+///
+/// `create_account` refuses addresses that already hold lamports, and anyone can send lamports to
+/// a PDA before it is created, so a pre-funded account is topped up to rent exemption, allocated
+/// and assigned instead.
+pub fn create_pda_account<'info>(
+    payer: AccountInfo<'info>,
+    account: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+    space: usize,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let rent_lamports = Rent::get()?.minimum_balance(space);
+    let lamports = account.lamports();
+    if lamports == 0 {
+        return system_program::create_account(
+            CpiContext::new_with_signer(
+                system_program,
+                system_program::CreateAccount {
+                    from: payer,
+                    to: account,
+                },
+                signer_seeds,
+            ),
+            rent_lamports,
+            space as u64,
+            &crate::ID,
+        );
+    }
+
+    if lamports < rent_lamports {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                system_program::Transfer {
+                    from: payer,
+                    to: account.clone(),
+                },
+            ),
+            rent_lamports - lamports,
+        )?;
+    }
+    system_program::allocate(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            system_program::Allocate {
+                account_to_allocate: account.clone(),
+            },
+            signer_seeds,
+        ),
+        space as u64,
+    )?;
+    system_program::assign(
+        CpiContext::new_with_signer(
+            system_program,
+            system_program::Assign {
+                account_to_assign: account,
+            },
+            signer_seeds,
+        ),
+        &crate::ID,
+    )
+}
+
+/// Fee withheld by the Token-2022 transfer-fee extension when `amount` of `mint` is transferred,
+/// zero for mints without the extension
+pub fn transfer_fee(mint: &AccountInfo, amount: u64) -> Result<u64> {
+    if *mint.owner != anchor_spl::token_2022::ID {
+        return Ok(0);
+    }
+
+    let mint_data = mint.try_borrow_data()?;
+    let mint = StateWithExtensions::<Mint>::unpack(&mint_data)?;
+    match mint.get_extension::<TransferFeeConfig>() {
+        Ok(transfer_fee_config) => Ok(transfer_fee_config
+            .calculate_epoch_fee(Clock::get()?.epoch, amount)
+            .ok_or(MathOverflow)?),
+        Err(_) => Ok(0),
+    }
+}
+
+/// Amount of `mint` to transfer for `post_fee_amount` to arrive after the Token-2022 transfer fee,
+/// `post_fee_amount` itself for mints without the extension
+pub fn pre_transfer_fee_amount(mint: &AccountInfo, post_fee_amount: u64) -> Result<u64> {
+    if *mint.owner != anchor_spl::token_2022::ID || post_fee_amount == 0 {
+        return Ok(post_fee_amount);
+    }
+
+    let mint_data = mint.try_borrow_data()?;
+    let mint = StateWithExtensions::<Mint>::unpack(&mint_data)?;
+    match mint.get_extension::<TransferFeeConfig>() {
+        Ok(transfer_fee_config) => {
+            let inverse_fee = transfer_fee_config
+                .calculate_inverse_epoch_fee(Clock::get()?.epoch, post_fee_amount)
+                .ok_or(MathOverflow)?;
+            Ok(post_fee_amount
+                .checked_add(inverse_fee)
+                .ok_or(MathOverflow)?)
+        }
+        Err(_) => Ok(post_fee_amount),
+    }
+}
+
+/// `bps` basis points of `amount`, rounded down
+pub fn bps_of(amount: u64, bps: u16) -> u64 {
+    ((amount as u128) * (bps as u128) / (BPS_DENOMINATOR as u128)) as u64
+}
+
+/// Moves `amount` of the quote token of an SPL-quoted launch, signed by `signer_seeds` when
+/// `authority` is a PDA
+pub fn transfer_quote<'info>(
+    quote_token_program: &Interface<'info, TokenInterface>,
+    quote_mint: &InterfaceAccount<'info, token_interface::Mint>,
+    from: AccountInfo<'info>,
+    to: AccountInfo<'info>,
+    authority: AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+    amount: u64,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            quote_token_program.to_account_info(),
+            token_interface::TransferChecked {
+                from,
+                mint: quote_mint.to_account_info(),
+                to,
+                authority,
+            },
+            signer_seeds,
+        ),
+        amount,
+        quote_mint.decimals,
+    )
+}
+
+/// Burns `amount` of `mint` from `account`, signed by `signer_seeds` when `authority` is a PDA
+pub fn burn_tokens<'info>(
+    token_program: &Interface<'info, TokenInterface>,
+    mint: &InterfaceAccount<'info, token_interface::Mint>,
+    account: AccountInfo<'info>,
+    authority: AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+    amount: u64,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+
+    token_interface::burn(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            token_interface::Burn {
+                mint: mint.to_account_info(),
+                from: account,
+                authority,
+            },
+            signer_seeds,
+        ),
+        amount,
+    )
+}
+
+/// Closes a token account owned by a PDA of this program, first harvesting any Token-2022 transfer
+/// fees withheld in it to the mint since the token program refuses to close it otherwise
+pub fn close_token_escrow<'info>(
+    token_program: AccountInfo<'info>,
+    mint: AccountInfo<'info>,
+    account: AccountInfo<'info>,
+    destination: AccountInfo<'info>,
+    authority: AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let has_transfer_fee = *mint.owner == anchor_spl::token_2022::ID && {
+        let mint_data = mint.try_borrow_data()?;
+        StateWithExtensions::<Mint>::unpack(&mint_data)?
+            .get_extension::<TransferFeeConfig>()
+            .is_ok()
+    };
+    if has_transfer_fee {
+        invoke(
+            &harvest_withheld_tokens_to_mint(&token_program.key(), &mint.key(), &[&account.key()])?,
+            &[mint, account.clone()],
+        )?;
+    }
+
+    token_interface::close_account(CpiContext::new_with_signer(
+        token_program,
+        token_interface::CloseAccount {
+            account,
+            destination,
+            authority,
+        },
+        signer_seeds,
+    ))
+}
+
+/// `1.0` in Q63 fixed point
+pub const Q63_ONE: u128 = 1 << 63;
+
+/// `ln(2)` in Q63 fixed point
+pub const LN_2_Q63: u128 = 6_393_154_322_601_327_829;
+
+/// `2^(2^-i)` in Q63 fixed point for `i` in `1..=32`
+const EXP2_FRACTIONS_Q63: [u128; 32] = [
+    13043817825332782212,
+    10968499650544839023,
+    10058158527438640870,
+    9631725603661576980,
+    9425337585438007767,
+    9323807973325342579,
+    9273454034941487980,
+    9248379135337035440,
+    9235867122425417552,
+    9229617465154500136,
+    9226494222562136016,
+    9224932997620185949,
+    9224152484218207414,
+    9223762252282022472,
+    9223567142504825507,
+    9223469589163912706,
+    9223420812880372951,
+    9223396424835331638,
+    9223384230836993048,
+    9223378133843869260,
+    9223375085348818742,
+    9223373561101671327,
+    9223372798978192080,
+    9223372417916476072,
+    9223372227385623972,
+    9223372132120199398,
+    9223372084487487480,
+    9223372060671131613,
+    9223372048762953702,
+    9223372042808864753,
+    9223372039831820280,
+    9223372038343298044,
+];
+
+/// `2^exponent` in Q63 fixed point, for an `exponent` in Q32 fixed point
+pub fn exp2_q63(exponent_q32: u128) -> Result<u128> {
+    let integer = exponent_q32 >> 32;
+    let fraction = exponent_q32 & u32::MAX as u128;
+
+    // multiply in `2^(2^-i)` for every set bit of the fraction
+    let mut result = Q63_ONE;
+    for (i, factor) in EXP2_FRACTIONS_Q63.iter().enumerate() {
+        if fraction & (1 << (31 - i)) != 0 {
+            result = (result * factor) >> 63;
+        }
+    }
+
+    if integer >= 64 {
+        return Err(MathOverflow.into());
+    }
+    Ok(result << integer)
+}
+
+/// Checks launch metadata against the limits of the Metaplex token metadata program, applied to
+/// both token standards so metadata can move between them
+pub fn validate_metadata(name: &str, symbol: &str, uri: &str) -> Result<()> {
+    for (field, value, max_len) in [
+        ("name", name, MAX_NAME_LENGTH),
+        ("symbol", symbol, MAX_SYMBOL_LENGTH),
+        ("uri", uri, MAX_URI_LENGTH),
+    ] {
+        if value.trim().is_empty() {
+            msg!(&format!("{field} must not be empty"));
+            return Err(ValueInvalid.into());
+        }
+        if value.len() > max_len {
+            msg!(
+                "{field} is {} bytes, at most {max_len} allowed",
+                value.len()
+            );
+            return Err(ValueTooLarge.into());
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::solana_program::{
+        entrypoint::ProgramResult,
+        instruction::Instruction,
+        program_error::ProgramError,
+        program_stubs::{set_syscall_stubs, SyscallStubs},
+        program_utils::limited_deserialize,
+        system_instruction::{SystemError, SystemInstruction},
+    };
+
+    /// Runs the system program instructions used by `create_pda_account` on the passed accounts,
+    /// with the same refusal of funded addresses as the real `create_account`
+    struct SystemProgramStubs;
+
+    impl SyscallStubs for SystemProgramStubs {
+        fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+            unsafe { *(var_addr as *mut Rent) = Rent::default() };
+            anchor_lang::solana_program::entrypoint::SUCCESS
+        }
+
+        fn sol_invoke_signed(
+            &self,
+            instruction: &Instruction,
+            account_infos: &[AccountInfo],
+            _signers_seeds: &[&[&[u8]]],
+        ) -> ProgramResult {
+            let account = |index: usize| {
+                account_infos
+                    .iter()
+                    .find(|info| *info.key == instruction.accounts[index].pubkey)
+                    .unwrap()
+            };
+            let move_lamports = |from: &AccountInfo, to: &AccountInfo, lamports: u64| {
+                **from.try_borrow_mut_lamports()? -= lamports;
+                **to.try_borrow_mut_lamports()? += lamports;
+                Ok::<_, ProgramError>(())
+            };
+            let allocate = |account: &AccountInfo, space: u64| {
+                if !account.data_is_empty() || *account.owner != system_program::ID {
+                    return Err(ProgramError::Custom(
+                        SystemError::AccountAlreadyInUse as u32,
+                    ));
+                }
+                *account.try_borrow_mut_data()? =
+                    Box::leak(vec![0; space as usize].into_boxed_slice());
+                Ok(())
+            };
+
+            match limited_deserialize(&instruction.data, 1024)
+                .map_err(|_| ProgramError::InvalidInstructionData)?
+            {
+                SystemInstruction::CreateAccount {
+                    lamports,
+                    space,
+                    owner,
+                } => {
+                    let (from, to) = (account(0), account(1));
+                    if to.lamports() > 0 {
+                        return Err(ProgramError::Custom(
+                            SystemError::AccountAlreadyInUse as u32,
+                        ));
+                    }
+                    move_lamports(from, to, lamports)?;
+                    allocate(to, space)?;
+                    to.assign(&owner);
+                }
+                SystemInstruction::Transfer { lamports } => {
+                    move_lamports(account(0), account(1), lamports)?;
+                }
+                SystemInstruction::Allocate { space } => allocate(account(0), space)?,
+                SystemInstruction::Assign { owner } => account(0).assign(&owner),
+                _ => return Err(ProgramError::InvalidInstructionData),
+            }
+            Ok(())
+        }
+    }
+
+    /// Lamports, data and owner of a test account
+    struct TestAccount {
+        key: Pubkey,
+        lamports: u64,
+        data: Vec<u8>,
+        owner: Pubkey,
+    }
+
+    impl TestAccount {
+        fn new(lamports: u64) -> Self {
+            Self {
+                key: Pubkey::new_unique(),
+                lamports,
+                data: vec![],
+                owner: system_program::ID,
+            }
+        }
+
+        fn info(&mut self, is_signer: bool) -> AccountInfo<'_> {
+            AccountInfo::new(
+                &self.key,
+                is_signer,
+                true,
+                &mut self.lamports,
+                &mut self.data,
+                &self.owner,
+                false,
+                0,
+            )
+        }
+    }
+
+    /// Creates a 100-byte PDA holding `prefunded_lamports`, returning its lamports, data length and
+    /// owner
+    fn create_prefunded_pda(prefunded_lamports: u64) -> Result<(u64, usize, Pubkey)> {
+        set_syscall_stubs(Box::new(SystemProgramStubs));
+
+        let mut payer = TestAccount::new(1_000_000_000);
+        let mut pda = TestAccount::new(prefunded_lamports);
+        let mut system = TestAccount::new(1);
+        system.key = system_program::ID;
+        system.owner = Pubkey::default();
+
+        let pda_info = pda.info(false);
+        create_pda_account(
+            payer.info(true),
+            pda_info.clone(),
+            system.info(false),
+            100,
+            &[&[b"pda"]],
+        )?;
+
+        let (lamports, len, owner) = (pda_info.lamports(), pda_info.data_len(), *pda_info.owner);
+        Ok((lamports, len, owner))
+    }
+
+    #[test]
+    fn create_pda_account_creates_unfunded_account() {
+        let rent_lamports = Rent::default().minimum_balance(100);
+        assert_eq!(
+            create_prefunded_pda(0).unwrap(),
+            (rent_lamports, 100, crate::ID)
+        );
+    }
+
+    #[test]
+    fn create_pda_account_tops_up_prefunded_account() {
+        let rent_lamports = Rent::default().minimum_balance(100);
+        assert_eq!(
+            create_prefunded_pda(1).unwrap(),
+            (rent_lamports, 100, crate::ID)
+        );
+    }
+
+    #[test]
+    fn create_pda_account_keeps_excess_prefunded_lamports() {
+        let rent_lamports = Rent::default().minimum_balance(100);
+        assert_eq!(
+            create_prefunded_pda(rent_lamports + 1).unwrap(),
+            (rent_lamports + 1, 100, crate::ID)
+        );
+    }
+
+    #[test]
+    fn load_amm_keys_reads_nonce_and_keys_at_amm_info_offsets() {
+        let keys: Vec<Pubkey> = (0..9).map(|_| Pubkey::new_unique()).collect();
+        let mut data = vec![0u8; 752];
+        data[8..16].copy_from_slice(&254u64.to_le_bytes());
+        for (index, key) in keys.iter().enumerate() {
+            data[336 + 32 * index..368 + 32 * index].copy_from_slice(key.as_ref());
+        }
+        let mut amm = TestAccount {
+            data,
+            owner: amm_anchor::ID,
+            ..TestAccount::new(1)
+        };
+
+        assert_eq!(
+            load_amm_keys(&amm.info(false)).unwrap(),
+            AmmKeys {
+                nonce: 254,
+                coin_vault: keys[0],
+                pc_vault: keys[1],
+                coin_vault_mint: keys[2],
+                pc_vault_mint: keys[3],
+                lp_mint: keys[4],
+                open_orders: keys[5],
+                market: keys[6],
+                market_program: keys[7],
+                target_orders: keys[8],
+            }
+        );
+
+        amm.data.truncate(751);
+        assert!(load_amm_keys(&amm.info(false)).is_err());
+    }
+
+    #[test]
+    fn exp2_q63_is_exact_at_integers() {
+        for integer in 0..64 {
+            assert_eq!(
+                exp2_q63((integer as u128) << 32).unwrap(),
+                Q63_ONE << integer
+            );
+        }
+        assert!(exp2_q63(64 << 32).is_err());
+    }
+
+    #[test]
+    fn exp2_q63_matches_f64() {
+        for exponent_q32 in (0..16u128 << 32).step_by(12_345_678_901) {
+            let result = exp2_q63(exponent_q32).unwrap() as f64 / Q63_ONE as f64;
+            let expected = (exponent_q32 as f64 / 2.0_f64.powi(32)).exp2();
+            assert!(
+                (result - expected).abs() <= expected * 1e-15,
+                "2^{exponent_q32}/2^32: {result} vs {expected}"
+            );
+        }
+    }
+
+    #[test]
+    fn exp2_q63_is_monotonic() {
+        for start in [0, 1 << 31, (1 << 32) - 40, 5 << 32] {
+            let mut previous = exp2_q63(start).unwrap();
+            for exponent_q32 in start + 1..start + 80 {
+                let result = exp2_q63(exponent_q32).unwrap();
+                assert!(result > previous, "decreases at {exponent_q32}");
+                previous = result;
+            }
+        }
+    }
+}
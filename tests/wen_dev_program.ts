@@ -34,9 +34,33 @@ describe("wen_dev_program", () => {
       platformSellFeeBps: 500, // Example fee: 5%
       pegasusBuyFeeBps: 200, // Example fee: 2%
       pegasusSellFeeBps: 200, // Example fee: 2%
+      bidCreatorShareBps: 5000, // Example split: 50% of winning bids to the creator
+      launchExpirySeconds: new anchor.BN(7 * 24 * 60 * 60), // Example expiry: 1 week
+      devBuyMaxBps: 500,
+      referralFeeBps: 2000,
+      maxPresalesPerCreator: 3,
+      metadataMigrationPolicy: { handToCreator: {} },
+      collectionMint: null,
+      curveOrderTipLamports: new anchor.BN(5000),
+      sandwichWindowSlots: new anchor.BN(0),
+      sandwichPolicy: { penalty: { feeBps: 500 } },
+      graduationLamports: new anchor.BN(85 * anchor.web3.LAMPORTS_PER_SOL), // Example target: 85 SOL
       lamportAmountConfig: { range: { min: new anchor.BN(1000), max: new anchor.BN(10000) } },
       tokenSupplyConfig: { range: { min: new anchor.BN(5000), max: new anchor.BN(20000) } },
       tokenDecimalsConfig: { range: { min: 6, max: 9 } },
+      presaleDelayConfig: { range: { min: new anchor.BN(0), max: new anchor.BN(24 * 60 * 60) } },
+      presaleDurationConfig: { range: { min: new anchor.BN(60), max: new anchor.BN(24 * 60 * 60) } },
+      transferFeeBpsConfig: { range: { min: 0, max: 500 } },
+      curveKindConfig: {
+        enum: [
+          { constantProduct: {} },
+          { linear: { finalPriceMultiple: 10 } },
+          { exponential: { doublings: 4 } },
+        ],
+      },
+      maxWalletBpsConfig: { range: { min: 50, max: 500 } },
+      maxWalletDurationConfig: { range: { min: new anchor.BN(0), max: new anchor.BN(24 * 60 * 60) } },
+      quoteMints: [],
     };
 
     // Send the transaction to configure the program.
@@ -62,6 +86,7 @@ describe("wen_dev_program", () => {
     assert.equal(configAccount.platformSellFeeBps, 500);
     assert.equal(configAccount.pegasusBuyFeeBps, 200);
     assert.equal(configAccount.pegasusSellFeeBps, 200);
+    assert.equal(configAccount.bidCreatorShareBps, 5000);
     assert.equal(parseFloat(configAccount.lamportAmountConfig.range.min.toString()), 1000);
     assert.equal(parseFloat(configAccount.lamportAmountConfig.range.max.toString()), 10000);
     assert.equal(parseFloat(configAccount.tokenSupplyConfig.range.min.toString()), 5000);